@@ -0,0 +1,291 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::path::PathBuf;
+
+use clap::{ArgAction, Parser, Subcommand, ValueEnum};
+
+use ifcfg_devname::exit::ExitCode;
+
+/* Directory with ifcfg files used by initscripts and NetworkManager */
+pub const CONFIG_DIR: &str = "/etc/sysconfig/network-scripts";
+
+/* How the new name of network interface is printed */
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /* Just the name, udev rules use it as $result */
+    #[default]
+    Plain,
+    /* KEY=value lines for IMPORT{program} udev rules */
+    Export,
+    /* Whole resolution including outcomes of all ifcfg files */
+    Json,
+    /* Whole resolution described for humans */
+    Explain,
+}
+
+/* How list of mappings is printed */
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ListFormat {
+    /* Aligned columns for humans */
+    #[default]
+    Table,
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    #[command(about = "List every hardware address to name mapping in ifcfg files")]
+    List {
+        #[arg(long, value_enum, default_value_t, help = "Output format")]
+        format: ListFormat,
+    },
+    #[command(
+        visible_alias = "by-name",
+        about = "Find hardware address and ifcfg file configuring given name"
+    )]
+    Reverse {
+        #[arg(value_name = "NAME", help = "Name of the interface, as set by DEVICE")]
+        name: String,
+
+        #[arg(long, value_enum, default_value_t, help = "Output format")]
+        format: ListFormat,
+    },
+    #[command(
+        about = "Resolve name of every network interface on the host and print the rename plan"
+    )]
+    Plan {
+        #[arg(long, value_enum, default_value_t, help = "Output format")]
+        format: ListFormat,
+    },
+    #[command(about = "Audit ifcfg files for issues breaking naming, fails when any is found")]
+    Lint,
+}
+
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "DIR",
+        default_value = CONFIG_DIR,
+        help = "Directory with ifcfg files"
+    )]
+    pub config_dir: PathBuf,
+
+    #[arg(
+        long,
+        value_name = "HWADDR",
+        help = "Hardware address to look for instead of reading it from the interface, the interface is still checked in sysfs"
+    )]
+    pub mac: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Name of the interface, by default it's taken from udev environment (INTERFACE, IFINDEX, DEVPATH)"
+    )]
+    pub interface: Option<String>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "DIR",
+        help = "Where sysfs is mounted [default: /sys]"
+    )]
+    pub sysfs_root: Option<PathBuf>,
+
+    #[arg(long, value_enum, default_value_t, help = "Output format")]
+    pub format: Format,
+
+    #[arg(
+        long,
+        conflicts_with = "format",
+        help = "Print udev properties for IMPORT{program}, same as --format export"
+    )]
+    pub export: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["format", "export"],
+        help = "Describe why each ifcfg file was or wasn't used, same as --format explain"
+    )]
+    pub explain: bool,
+
+    #[arg(
+        short,
+        long,
+        global = true,
+        action = ArgAction::Count,
+        help = "Log more details, can be repeated"
+    )]
+    pub verbose: u8,
+
+    #[arg(
+        short,
+        long,
+        global = true,
+        action = ArgAction::Count,
+        conflicts_with = "verbose",
+        help = "Log less, can be repeated"
+    )]
+    pub quiet: u8,
+
+    /* Deprecated form used by tests before named options existed: <CONFIG_DIR> <HWADDR> */
+    #[arg(hide = true, num_args = 0..=2)]
+    pub legacy: Vec<String>,
+}
+
+impl Cli {
+    /* clap exits with 2 on invalid usage, which would be mistaken for ExitCode::NoMatch */
+    pub fn parse_args() -> Cli {
+        let mut cli = Cli::try_parse().unwrap_or_else(|err| {
+            let _ = err.print();
+
+            if err.use_stderr() {
+                ExitCode::Usage.exit()
+            } else {
+                ExitCode::Found.exit()
+            }
+        });
+
+        cli.apply_legacy();
+        cli
+    }
+
+    pub fn format(&self) -> Format {
+        if self.export {
+            Format::Export
+        } else if self.explain {
+            Format::Explain
+        } else {
+            self.format
+        }
+    }
+
+    /* Log level relative to default, negative values make logging quieter */
+    pub fn verbosity(&self) -> i8 {
+        self.verbose as i8 - self.quiet as i8
+    }
+
+    /* Positional form is only complete with both values, logger isn't ready yet so error goes to stderr */
+    fn apply_legacy(&mut self) {
+        match self.legacy.as_slice() {
+            [] => (),
+            [config_dir, mac] => {
+                self.config_dir = PathBuf::from(config_dir);
+                self.mac = Some(mac.clone());
+            }
+            _ => {
+                eprintln!("error: positional arguments <CONFIG_DIR> <HWADDR> have to be given together, use --config-dir and --mac instead");
+                ExitCode::Usage.exit()
+            }
+        }
+    }
+
+    pub fn is_legacy(&self) -> bool {
+        !self.legacy.is_empty()
+    }
+}
+
+#[cfg(test)]
+pub mod should {
+    use super::*;
+
+    #[test]
+    fn parse_command_line() {
+        let cli = Cli::try_parse_from([
+            "ifcfg-devname",
+            "--config-dir",
+            "./ifcfgs",
+            "--mac",
+            "AA:BB:CC:DD:EE:FF",
+            "-vv",
+        ])
+        .unwrap();
+
+        assert_eq!(cli.config_dir, PathBuf::from("./ifcfgs"));
+        assert_eq!(cli.mac.as_deref(), Some("AA:BB:CC:DD:EE:FF"));
+        assert_eq!(cli.format(), Format::Plain);
+        assert_eq!(cli.verbosity(), 2);
+        assert!(!cli.is_legacy());
+    }
+
+    #[test]
+    fn parse_legacy_command_line() {
+        let mut cli =
+            Cli::try_parse_from(["ifcfg-devname", "./ifcfgs", "AA:BB:CC:DD:EE:FF"]).unwrap();
+        cli.apply_legacy();
+
+        assert!(cli.is_legacy());
+        assert_eq!(cli.config_dir, PathBuf::from("./ifcfgs"));
+        assert_eq!(cli.mac.as_deref(), Some("AA:BB:CC:DD:EE:FF"));
+        assert_eq!(
+            Cli::try_parse_from(["ifcfg-devname"]).unwrap().config_dir,
+            PathBuf::from(CONFIG_DIR)
+        );
+        assert!(Cli::try_parse_from(["ifcfg-devname", "-v", "-q"]).is_err());
+    }
+
+    #[test]
+    fn parse_list_command() {
+        let cli = Cli::try_parse_from([
+            "ifcfg-devname",
+            "list",
+            "--config-dir",
+            "./ifcfgs",
+            "--format",
+            "csv",
+        ])
+        .unwrap();
+
+        assert!(matches!(
+            cli.command,
+            Some(Command::List {
+                format: ListFormat::Csv
+            })
+        ));
+        assert_eq!(cli.config_dir, PathBuf::from("./ifcfgs"));
+        assert!(!cli.is_legacy());
+        assert!(matches!(
+            Cli::try_parse_from(["ifcfg-devname", "lint"])
+                .unwrap()
+                .command,
+            Some(Command::Lint)
+        ));
+    }
+
+    #[test]
+    fn parse_reverse_command() {
+        for command in ["reverse", "by-name"] {
+            let cli = Cli::try_parse_from(["ifcfg-devname", command, "storage0"]).unwrap();
+
+            assert!(matches!(
+                cli.command,
+                Some(Command::Reverse { ref name, format: ListFormat::Table }) if name == "storage0"
+            ));
+        }
+        assert!(Cli::try_parse_from(["ifcfg-devname", "reverse"]).is_err());
+    }
+
+    #[test]
+    fn parse_export_format() {
+        let export = Cli::try_parse_from(["ifcfg-devname", "--export"]).unwrap();
+        let format = Cli::try_parse_from(["ifcfg-devname", "--format", "export"]).unwrap();
+
+        assert_eq!(export.format(), Format::Export);
+        assert_eq!(format.format(), Format::Export);
+        assert!(Cli::try_parse_from(["ifcfg-devname", "--export", "--format", "plain"]).is_err());
+        assert_eq!(
+            Cli::try_parse_from(["ifcfg-devname", "--explain"])
+                .unwrap()
+                .format(),
+            Format::Explain
+        );
+        assert!(Cli::try_parse_from(["ifcfg-devname", "--explain", "--export"]).is_err());
+    }
+}
@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::io;
+use std::mem;
+
+use crate::hwaddr::MAX_ADDR_LEN;
+
+/* Get permanent hardware address, see <linux/ethtool.h> */
+const ETHTOOL_GPERMADDR: u32 = 0x00000020;
+
+/* struct ethtool_perm_addr with space for the longest hw address */
+#[repr(C)]
+struct EthtoolPermAddr {
+    cmd: u32,
+    size: u32,
+    data: [u8; MAX_ADDR_LEN],
+}
+
+/* Ask kernel for permanent (burned-in) hw address of network interface using SIOCETHTOOL ioctl */
+pub fn permanent_address(interface: &str) -> io::Result<Vec<u8>> {
+    /* SAFETY: ifreq is plain C struct, all zeroes is valid value */
+    let mut ifr: libc::ifreq = unsafe { mem::zeroed() };
+
+    if interface.is_empty() || interface.len() >= ifr.ifr_name.len() {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput));
+    }
+
+    for (dst, src) in ifr.ifr_name.iter_mut().zip(interface.bytes()) {
+        *dst = src as libc::c_char;
+    }
+
+    let mut perm_addr = EthtoolPermAddr {
+        cmd: ETHTOOL_GPERMADDR,
+        size: MAX_ADDR_LEN as u32,
+        data: [0; MAX_ADDR_LEN],
+    };
+    ifr.ifr_ifru.ifru_data = &mut perm_addr as *mut EthtoolPermAddr as *mut libc::c_char;
+
+    /* SAFETY: plain socket() call, returned descriptor is checked and closed below */
+    let socket = unsafe { libc::socket(libc::AF_INET, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, 0) };
+    if socket < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    /* SAFETY: ifr and perm_addr outlive the call and perm_addr.size describes capacity of perm_addr.data */
+    let result = unsafe { libc::ioctl(socket, libc::SIOCETHTOOL, &mut ifr) };
+    let error = io::Error::last_os_error();
+
+    /* SAFETY: socket is valid descriptor owned by this function */
+    unsafe { libc::close(socket) };
+
+    if result < 0 {
+        return Err(error);
+    }
+
+    let size = (perm_addr.size as usize).min(MAX_ADDR_LEN);
+    Ok(perm_addr.data[..size].to_vec())
+}
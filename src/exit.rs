@@ -0,0 +1,79 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::udev::UdevError;
+use crate::MacLookupError;
+
+/* Exit codes of ifcfg-devname, they are stable so udev rules and monitoring can tell them apart */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitCode {
+    /* New name was found and printed on standard output */
+    Found = 0,
+    /* Nothing is configured for the interface, it keeps its name */
+    NoMatch = 2,
+    /* ifcfg files can't be read or they contradict each other */
+    ConfigError = 3,
+    /* Interface or its hw address can't be obtained */
+    InterfaceLookup = 4,
+    /* Matching ifcfg file sets DEVICE kernel refuses to use */
+    InvalidName = 5,
+    /* New name already belongs to another interface, rename would fail with EEXIST */
+    NameTaken = 6,
+    /* Program was run with invalid arguments or environment, see EX_USAGE in <sysexits.h> */
+    Usage = 64,
+}
+
+impl ExitCode {
+    pub fn code(self) -> i32 {
+        self as i32
+    }
+
+    pub fn exit(self) -> ! {
+        std::process::exit(self.code())
+    }
+}
+
+/* Interface without hw address can't be looked up in ifcfg files, rules can skip such interfaces by type */
+impl From<&MacLookupError> for ExitCode {
+    fn from(err: &MacLookupError) -> Self {
+        match err {
+            MacLookupError::InvalidAddress(_, _) => ExitCode::Usage,
+            MacLookupError::NotFound(_)
+            | MacLookupError::NoAddress(_)
+            | MacLookupError::PermissionDenied(_)
+            | MacLookupError::Io(_, _) => ExitCode::InterfaceLookup,
+        }
+    }
+}
+
+impl From<&UdevError> for ExitCode {
+    fn from(err: &UdevError) -> Self {
+        match err {
+            UdevError::MissingInterface => ExitCode::Usage,
+            UdevError::NotUnicode(_) => ExitCode::InterfaceLookup,
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod should {
+    use super::*;
+
+    #[test]
+    fn map_errors_to_exit_codes() {
+        assert_eq!(ExitCode::Found.code(), 0);
+        assert_eq!(ExitCode::NameTaken.code(), 6);
+        assert_eq!(ExitCode::Usage.code(), 64);
+        assert_eq!(
+            ExitCode::from(&MacLookupError::NoAddress(String::from("tun0"))),
+            ExitCode::InterfaceLookup
+        );
+        assert_eq!(
+            ExitCode::from(&MacLookupError::NotFound(String::from("eth0"))),
+            ExitCode::InterfaceLookup
+        );
+        assert_eq!(
+            ExitCode::from(&UdevError::MissingInterface),
+            ExitCode::Usage
+        );
+    }
+}
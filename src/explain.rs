@@ -0,0 +1,57 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use ifcfg_devname::report::{MacOrigin, Report};
+
+/* Print the whole resolution step by step, same steps as naming takes */
+pub fn print(report: &Report) {
+    let origin = match report.mac_origin {
+        Some(MacOrigin::Current) => format!("current hw address of '{}'", report.interface),
+        Some(MacOrigin::Permanent) => format!("permanent hw address of '{}'", report.interface),
+        Some(MacOrigin::CommandLine) => String::from("given by --mac"),
+        None => String::from("can't be obtained"),
+    };
+
+    println!("Interface: {}", report.interface);
+    println!(
+        "MAC address: {} ({})",
+        report.mac_address.as_deref().unwrap_or("unknown"),
+        origin
+    );
+
+    println!("ifcfg files:");
+    if report.files.is_empty() && report.ignored.is_empty() {
+        println!("  none");
+    }
+
+    for file in &report.files {
+        println!(
+            "  {}: {}: {}",
+            file.path.display(),
+            file.outcome,
+            file.reason
+        );
+    }
+
+    for file in &report.ignored {
+        println!(
+            "  {}: ignored: files ending with '{}' aren't ifcfg files",
+            file.path.display(),
+            file.suffix
+        );
+    }
+
+    if !report.warnings.is_empty() {
+        let warnings: Vec<String> = report
+            .warnings
+            .iter()
+            .map(|warning| warning.to_string())
+            .collect();
+        println!("Warnings: {}", warnings.join(", "));
+    }
+
+    if let Some(collision) = &report.collision {
+        println!("Collision: {}", collision);
+    }
+
+    println!("Decision: {}", report.decision);
+}
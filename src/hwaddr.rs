@@ -0,0 +1,229 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::fmt;
+use std::str::FromStr;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+/* Maximum length of hardware address, see <linux/netdevice.h> */
+pub const MAX_ADDR_LEN: usize = 32;
+
+/* Length of Ethernet hardware address (EUI-48), see <linux/if_ether.h> */
+pub const ETH_ADDR_LEN: usize = 6;
+
+/* Length of EUI-64 hardware address, e.g. FireWire or IEEE 802.15.4 */
+pub const EUI64_ADDR_LEN: usize = 8;
+
+/* Length of IPoIB hardware address - 4 bytes of QPN and 16 bytes of GID */
+pub const INFINIBAND_ADDR_LEN: usize = 20;
+
+/* Length of InfiniBand port GUID - last 8 bytes of IPoIB hardware address */
+const INFINIBAND_GUID_LEN: usize = 8;
+
+/* Notations of hw address accepted in ifcfg files */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Notation {
+    /* 00:1b:44:11:3a:b7 */
+    Canonical,
+    /* 0:1b:44:1:3a:b7 */
+    ShortOctets,
+    /* 00-1b-44-11-3a-b7 */
+    Dash,
+    /* 001b.4411.3ab7 */
+    Dot,
+    /* 001b44113ab7 */
+    Bare,
+}
+
+impl Notation {
+    pub fn is_canonical(&self) -> bool {
+        *self == Notation::Canonical
+    }
+}
+
+impl fmt::Display for Notation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Notation::Canonical => write!(f, "colon-separated octets"),
+            Notation::ShortOctets => write!(f, "colon-separated octets without leading zeros"),
+            Notation::Dash => write!(f, "dash-separated octets"),
+            Notation::Dot => write!(f, "dot-separated groups of four digits"),
+            Notation::Bare => write!(f, "digits without separators"),
+        }
+    }
+}
+
+/* Link-layer address of any length (Ethernet, InfiniBand, etc.) */
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HwAddress(Vec<u8>);
+
+impl HwAddress {
+    pub fn new(bytes: &[u8]) -> HwAddress {
+        HwAddress(bytes.to_vec())
+    }
+
+    /* Parse hw address written in any of supported notations and report which one was used */
+    pub fn parse(value: &str) -> Result<(HwAddress, Notation), String> {
+        lazy_static! {
+            /* Check that hw address consists of octets separated by `:` or `-`
+             * regex: ^[0-9A-Fa-f]{1,2}([:-][0-9A-Fa-f]{1,2})*$
+             * ^[0-9A-Fa-f]{1,2} - first 8-bits expressed in hexadecimal system, leading zero can be omitted ; case insensitive
+             * ([:-][0-9A-Fa-f]{1,2})*$ - following with any number of 8-bits separated with `:` or `-` character
+             * example: 00:1b:44:11:3A:B7 | 0:1b:44:1:3a:b7 | 00-1B-44-11-3A-B7
+             *          ^^^^^^^^^^^^^^^^^   ^^^^^^^^^^^^^^^   ^^^^^^^^^^^^^^^^^
+             *              canonical         short octets           dash */
+            static ref REGEX_OCTETS: Regex = Regex::new(r"^[0-9A-Fa-f]{1,2}([:-][0-9A-Fa-f]{1,2})*$").unwrap();
+
+            /* Check that hw address consists of dot-separated groups of 16-bits (Cisco notation)
+             * regex: ^[0-9A-Fa-f]{4}(\.[0-9A-Fa-f]{4})+$
+             * example: 001b.4411.3ab7 */
+            static ref REGEX_DOT: Regex = Regex::new(r"^[0-9A-Fa-f]{4}(\.[0-9A-Fa-f]{4})+$").unwrap();
+
+            /* Check that hw address consists of hexadecimal digits without any separator
+             * regex: ^([0-9A-Fa-f]{2})+$
+             * example: 001b44113ab7 */
+            static ref REGEX_BARE: Regex = Regex::new(r"^([0-9A-Fa-f]{2})+$").unwrap();
+        }
+
+        let (octets, notation): (Vec<&str>, Notation) = if REGEX_OCTETS.is_match(value) {
+            if value.contains('-') && value.contains(':') {
+                return Err(format!("'{}' mixes `:` and `-` separators", value));
+            }
+
+            if value.contains('-') {
+                (value.split('-').collect(), Notation::Dash)
+            } else {
+                let octets: Vec<&str> = value.split(':').collect();
+                if octets.iter().any(|octet| octet.len() != 2) {
+                    (octets, Notation::ShortOctets)
+                } else {
+                    (octets, Notation::Canonical)
+                }
+            }
+        } else if REGEX_DOT.is_match(value) {
+            let octets = value
+                .split('.')
+                .flat_map(|group| [&group[..2], &group[2..]])
+                .collect();
+            (octets, Notation::Dot)
+        } else if REGEX_BARE.is_match(value) {
+            let octets = (0..value.len())
+                .step_by(2)
+                .map(|index| &value[index..index + 2])
+                .collect();
+            (octets, Notation::Bare)
+        } else {
+            return Err(format!("'{}' isn't valid hw address", value));
+        };
+
+        /* Only hw addresses ifcfg files can be written for, anything else is most likely a typo */
+        if ![ETH_ADDR_LEN, EUI64_ADDR_LEN, INFINIBAND_ADDR_LEN].contains(&octets.len()) {
+            return Err(format!(
+                "'{}' has {} bytes, expected {} (Ethernet), {} (EUI-64) or {} (InfiniBand)",
+                value,
+                octets.len(),
+                ETH_ADDR_LEN,
+                EUI64_ADDR_LEN,
+                INFINIBAND_ADDR_LEN
+            ));
+        }
+
+        let bytes = octets
+            .iter()
+            .map(|octet| u8::from_str_radix(octet, 16).unwrap())
+            .collect();
+
+        Ok((HwAddress(bytes), notation))
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn is_infiniband(&self) -> bool {
+        self.0.len() == INFINIBAND_ADDR_LEN
+    }
+
+    /* Compare hw address from ifcfg file with hw address of interface
+     * InfiniBand QPN and subnet prefix may change, so only port GUID is compared, same as initscripts did */
+    pub fn matches(&self, other: &HwAddress) -> bool {
+        if self.is_infiniband() && other.is_infiniband() {
+            self.0[INFINIBAND_ADDR_LEN - INFINIBAND_GUID_LEN..]
+                == other.0[INFINIBAND_ADDR_LEN - INFINIBAND_GUID_LEN..]
+        } else {
+            self == other
+        }
+    }
+}
+
+impl FromStr for HwAddress {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        HwAddress::parse(value).map(|(address, _)| address)
+    }
+}
+
+impl fmt::Display for HwAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let octets: Vec<String> = self.0.iter().map(|byte| format!("{:02x}", byte)).collect();
+        write!(f, "{}", octets.join(":"))
+    }
+}
+
+#[cfg(test)]
+pub mod should {
+    use super::*;
+
+    const IB_ADDRESS: &str = "80:00:02:08:fe:80:00:00:00:00:00:00:00:02:c9:03:00:0a:bc:01";
+
+    #[test]
+    fn parse_hw_address() {
+        let ethernet: HwAddress = "AA:BB:CC:DD:EE:3F".parse().unwrap();
+        let infiniband: HwAddress = IB_ADDRESS.parse().unwrap();
+
+        assert_eq!(ethernet.bytes(), &[0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0x3f]);
+        assert_eq!(ethernet.to_string(), "aa:bb:cc:dd:ee:3f");
+        assert!(infiniband.is_infiniband());
+        assert_eq!(infiniband.to_string(), IB_ADDRESS);
+        assert!("AA:BB:CC:DD:EE".parse::<HwAddress>().is_err());
+        assert!("00:1b:44:ff:fe:11:3a:b7".parse::<HwAddress>().is_ok());
+        assert!("00:1b:44:11:3a:b7:01".parse::<HwAddress>().is_err());
+        assert!("AA:BB:CC:DD:EE:".parse::<HwAddress>().is_err());
+    }
+
+    #[test]
+    fn parse_hw_address_notations() {
+        let expected: HwAddress = "00:1b:44:11:3a:b7".parse().unwrap();
+
+        for (value, notation) in [
+            ("00:1B:44:11:3A:B7", Notation::Canonical),
+            ("0:1b:44:11:3a:b7", Notation::ShortOctets),
+            ("00-1B-44-11-3A-B7", Notation::Dash),
+            ("001b.4411.3ab7", Notation::Dot),
+            ("001b44113ab7", Notation::Bare),
+        ] {
+            assert_eq!(HwAddress::parse(value), Ok((expected.clone(), notation)));
+        }
+
+        assert!(HwAddress::parse("00:1b-44:11:3a:b7").is_err());
+        assert!(HwAddress::parse("001b.4411.3ab").is_err());
+        assert!(HwAddress::parse("001b44113ab").is_err());
+        assert!(HwAddress::parse("00:1b:44:11:3a:g7").is_err());
+    }
+
+    #[test]
+    fn match_infiniband_port_guid() {
+        let configured: HwAddress = IB_ADDRESS.parse().unwrap();
+        let current: HwAddress = "a0:00:03:00:fe:80:00:00:00:00:00:00:00:02:c9:03:00:0a:bc:01"
+            .parse()
+            .unwrap();
+        let other_port: HwAddress = "80:00:02:08:fe:80:00:00:00:00:00:00:00:02:c9:03:00:0a:bc:02"
+            .parse()
+            .unwrap();
+
+        assert!(configured.matches(&current));
+        assert!(!configured.matches(&other_port));
+    }
+}
@@ -1,13 +1,32 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 use std::error;
-use std::str::FromStr;
-
-use mac_address::{mac_address_by_name, MacAddress};
+use std::fmt;
+use std::io;
 
 use lazy_static::lazy_static;
+use log::*;
 use regex::Regex;
 
+mod ethtool;
+pub mod exit;
+pub mod hwaddr;
+pub mod naming;
+pub mod options;
+pub mod parser;
+pub mod report;
+pub mod resolver;
+pub mod shell;
+pub mod sysfs;
+pub mod udev;
+
+pub use hwaddr::HwAddress;
+pub use parser::IfcfgFile;
+pub use sysfs::Sysfs;
+
+use options::{MacSource, MacaddrMode, Options};
+use report::MacOrigin;
+
 /* Check if new devname is equal to kernel standard devname (eth0, etc.) */
 pub fn is_like_kernel_name(new_devname: &str) -> bool {
     lazy_static! {
@@ -24,43 +43,226 @@ pub fn is_like_kernel_name(new_devname: &str) -> bool {
     IS_NEW_DEVNAME_ETH_LIKE.is_match(new_devname)
 }
 
-pub fn is_test_mode(params: &[String], number_params_required: usize) -> bool {
-    params.len() >= number_params_required
+/* Size of buffer for network interface name including terminating NUL, see <linux/if.h> */
+pub const IFNAMSIZ: usize = 16;
+
+/* Reasons why kernel refuses to use given name for network interface */
+#[derive(Debug, PartialEq, Eq)]
+pub enum InvalidNameReason {
+    Empty,
+    TooLong(usize),
+    DotName,
+    Slash,
+    Colon,
+    Whitespace,
+}
+
+impl fmt::Display for InvalidNameReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InvalidNameReason::Empty => write!(f, "name is empty"),
+            InvalidNameReason::TooLong(length) => write!(
+                f,
+                "name is {} bytes long, maximum is {} bytes",
+                length,
+                IFNAMSIZ - 1
+            ),
+            InvalidNameReason::DotName => write!(f, "name can't be '.' or '..'"),
+            InvalidNameReason::Slash => write!(f, "name can't contain '/'"),
+            InvalidNameReason::Colon => write!(f, "name can't contain ':'"),
+            InvalidNameReason::Whitespace => write!(f, "name can't contain whitespace"),
+        }
+    }
+}
+
+/* Check if new devname is accepted by kernel, follows dev_valid_name() from net/core/dev.c */
+pub fn validate_devname(new_devname: &str) -> Result<(), InvalidNameReason> {
+    if new_devname.is_empty() {
+        return Err(InvalidNameReason::Empty);
+    }
+
+    if new_devname.len() >= IFNAMSIZ {
+        return Err(InvalidNameReason::TooLong(new_devname.len()));
+    }
+
+    if new_devname == "." || new_devname == ".." {
+        return Err(InvalidNameReason::DotName);
+    }
+
+    for byte in new_devname.bytes() {
+        match byte {
+            b'/' => return Err(InvalidNameReason::Slash),
+            b':' => return Err(InvalidNameReason::Colon),
+            /* Kernel isspace() also treats 0xA0 (Latin-1 non-breaking space) as whitespace */
+            b' ' | b'\t' | b'\n' | 0x0b | 0x0c | b'\r' | 0xa0 => {
+                return Err(InvalidNameReason::Whitespace)
+            }
+            _ => continue,
+        }
+    }
+
+    Ok(())
+}
+
+/* Reasons why hw address of network interface can't be obtained */
+#[derive(Debug)]
+pub enum MacLookupError {
+    /* Interface doesn't exist (anymore), e.g. it vanished during hotplug */
+    NotFound(String),
+    /* Interface has no link-layer address (tun, wireguard, ip6tnl, etc.) */
+    NoAddress(String),
+    PermissionDenied(String),
+    Io(String, io::Error),
+    /* hw address given on command line can't be parsed */
+    InvalidAddress(String, String),
 }
 
-pub fn get_mac_address(
-    is_test_mode: bool,
-    args: &[String],
-    index: usize,
+impl fmt::Display for MacLookupError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MacLookupError::NotFound(name) => write!(f, "interface '{}' doesn't exist", name),
+            MacLookupError::NoAddress(name) => {
+                write!(f, "interface '{}' has no link-layer address", name)
+            }
+            MacLookupError::PermissionDenied(name) => write!(
+                f,
+                "permission denied to read hw address of interface '{}'",
+                name
+            ),
+            MacLookupError::Io(name, err) => write!(
+                f,
+                "fail to read hw address of interface '{}': {}",
+                name, err
+            ),
+            MacLookupError::InvalidAddress(value, reason) => {
+                write!(f, "invalid hw address '{}': {}", value, reason)
+            }
+        }
+    }
+}
+
+impl error::Error for MacLookupError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            MacLookupError::Io(_, err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+pub fn get_mac_address(sysfs: &Sysfs, kernel_name: &str) -> Result<HwAddress, MacLookupError> {
+    /* sysfs reports addresses of any length (InfiniBand) and tells whether the interface has any */
+    sysfs
+        .interface(kernel_name)?
+        .address
+        .ok_or_else(|| MacLookupError::NoAddress(kernel_name.to_owned()))
+}
+
+/* Permanent (burned-in) hw address of network interface, None when driver doesn't provide it */
+pub fn get_permanent_mac_address(
+    sysfs: &Sysfs,
     kernel_name: &str,
-) -> Result<MacAddress, Box<dyn error::Error>> {
-    let mac_address = if is_test_mode {
-        let mac_address = args[index].clone();
-        MacAddress::from_str(&mac_address)?
-    } else {
-        match mac_address_by_name(kernel_name)? {
-            Some(mac) => mac,
-            None => panic!(),
+) -> Result<Option<HwAddress>, MacLookupError> {
+    /* Some kernels export permanent hw address in sysfs, that works also for sysfs of other root */
+    if let Some(address) = sysfs.permanent_address(kernel_name) {
+        return Ok(Some(address));
+    }
+
+    /* ioctl asks the running kernel, its answer would be unrelated to interfaces of other sysfs root */
+    if !sysfs.is_host() {
+        sysfs.interface(kernel_name)?;
+        return Ok(None);
+    }
+
+    match ethtool::permanent_address(kernel_name) {
+        Ok(address) if address.iter().any(|byte| *byte != 0) => Ok(Some(HwAddress::new(&address))),
+        Ok(_) => Ok(None),
+        Err(err) => match err.raw_os_error() {
+            /* Driver doesn't implement ETHTOOL_GPERMADDR */
+            Some(libc::EOPNOTSUPP) => Ok(None),
+            Some(libc::ENODEV) => Err(MacLookupError::NotFound(kernel_name.to_owned())),
+            Some(libc::EPERM) | Some(libc::EACCES) => {
+                Err(MacLookupError::PermissionDenied(kernel_name.to_owned()))
+            }
+            _ => Err(MacLookupError::Io(kernel_name.to_owned(), err)),
+        },
+    }
+}
+
+/* Other interface that already has given name, renaming to it would fail with EEXIST */
+pub fn find_name_owner(sysfs: &Sysfs, name: &str, kernel_name: &str) -> Option<sysfs::Interface> {
+    if name == kernel_name {
+        return None;
+    }
+
+    sysfs.interface(name).ok()
+}
+
+/* hw address matched against HWADDR
+ * IFCFG_DEVNAME_MAC_SOURCE=permanent uses the permanent hw address always, IFCFG_DEVNAME_MACADDR_MODE=permanent
+ * only when the current one was assigned by MACADDR of some ifcfg file; the former makes the latter redundant */
+pub fn get_naming_mac_address(
+    sysfs: &Sysfs,
+    options: &Options,
+    kernel_name: &str,
+    mac_address: HwAddress,
+    mac_origin: MacOrigin,
+    ifcfgs: &[IfcfgFile],
+) -> (HwAddress, MacOrigin) {
+    let wants_permanent = match options.mac_source {
+        MacSource::Permanent => true,
+        /* Current hw address was assigned using MACADDR, HWADDR describes permanent hw address */
+        MacSource::Current => {
+            options.macaddr_mode == MacaddrMode::Permanent
+                && ifcfgs.iter().any(
+                    |ifcfg| matches!(ifcfg.macaddr(), Ok(Some(macaddr)) if macaddr == mac_address),
+                )
         }
     };
 
-    Ok(mac_address)
+    if !wants_permanent {
+        return (mac_address, mac_origin);
+    }
+
+    match lookup_permanent_mac_address(sysfs, kernel_name, &mac_address) {
+        Some(permanent) => {
+            if permanent != mac_address {
+                info!(
+                    "Using permanent MAC address '{}' of '{}' instead of current MAC address '{}'",
+                    permanent, kernel_name, mac_address
+                );
+            }
+            (permanent, MacOrigin::Permanent)
+        }
+        None => (mac_address, mac_origin),
+    }
+}
+
+/* Permanent hw address when it's available, otherwise reason is logged and the current one stays in use */
+fn lookup_permanent_mac_address(
+    sysfs: &Sysfs,
+    kernel_name: &str,
+    current: &HwAddress,
+) -> Option<HwAddress> {
+    let reason = match get_permanent_mac_address(sysfs, kernel_name) {
+        Ok(Some(permanent)) => return Some(permanent),
+        Ok(None) => String::from("driver doesn't provide it"),
+        Err(err) => err.to_string(),
+    };
+
+    warn!(
+        "Permanent MAC address of '{}' isn't available ({}), falling back to current MAC address '{}'",
+        kernel_name, reason, current
+    );
+
+    None
 }
 
 #[cfg(test)]
 pub mod should {
     use super::*;
 
-    #[test]
-    #[should_panic]
-    fn check_for_test_mode() {
-        const NUMBER_PARAMS_REQUIRED: usize = 3;
-        const ARGS: Vec<String> = Vec::new();
-
-        let is_test_mode = is_test_mode(&ARGS, NUMBER_PARAMS_REQUIRED);
-
-        assert!(is_test_mode);
-    }
+    use std::str::FromStr;
 
     #[test]
     fn check_if_is_like_kernel_name() {
@@ -72,13 +274,134 @@ pub mod should {
     }
 
     #[test]
-    #[should_panic]
+    fn check_if_is_valid_devname() {
+        assert_eq!(validate_devname("storage-backend"), Ok(()));
+        assert_eq!(
+            validate_devname("storage-backend-01"),
+            Err(InvalidNameReason::TooLong(18))
+        );
+        assert_eq!(validate_devname(""), Err(InvalidNameReason::Empty));
+        assert_eq!(validate_devname(".."), Err(InvalidNameReason::DotName));
+        assert_eq!(validate_devname("lan/0"), Err(InvalidNameReason::Slash));
+        assert_eq!(validate_devname("lan0:1"), Err(InvalidNameReason::Colon));
+        assert_eq!(
+            validate_devname("lan 0"),
+            Err(InvalidNameReason::Whitespace)
+        );
+    }
+
+    #[test]
     fn not_get_mac_address() {
-        const IS_TEST_MODE: bool = false;
-        const ARGS: &Vec<String> = &Vec::new();
-        const INDEX: usize = 3;
         let kernel_name: String = String::from_str("this-should-fail").unwrap();
 
-        let _ = get_mac_address(IS_TEST_MODE, ARGS, INDEX, &kernel_name);
+        let result = get_mac_address(&Sysfs::default(), &kernel_name);
+
+        assert!(matches!(result, Err(MacLookupError::NotFound(name)) if name == kernel_name));
+    }
+
+    #[test]
+    fn get_infiniband_mac_address() {
+        let sysfs = Sysfs::new(std::path::Path::new("./tests/unit_test_data/sysfs"));
+
+        let mac_address = get_mac_address(&sysfs, "ib0").unwrap();
+
+        /* Whole 20 bytes are kept, the ifcfg file differs only in QPN */
+        assert_eq!(mac_address.bytes().len(), hwaddr::INFINIBAND_ADDR_LEN);
+        assert_eq!(
+            mac_address.to_string(),
+            "80:00:02:08:fe:80:00:00:00:00:00:00:00:02:c9:03:00:0a:bc:01"
+        );
+        assert!(mac_address.matches(
+            &"a0:00:03:00:fe:80:00:00:00:00:00:00:00:02:c9:03:00:0a:bc:01"
+                .parse()
+                .unwrap()
+        ));
+    }
+
+    #[test]
+    fn find_owner_of_name() {
+        let sysfs = Sysfs::new(std::path::Path::new("./tests/unit_test_data/sysfs"));
+
+        assert_eq!(
+            find_name_owner(&sysfs, "enp0s31f6", "eth0").map(|owner| owner.address),
+            Some("00:1b:44:11:3a:b7".parse().ok())
+        );
+        assert!(find_name_owner(&sysfs, "enp0s31f6", "enp0s31f6").is_none());
+        assert!(find_name_owner(&sysfs, "lan0", "eth0").is_none());
+    }
+
+    #[test]
+    fn not_get_permanent_mac_address() {
+        let result = get_permanent_mac_address(&Sysfs::default(), "should-fail0");
+
+        assert!(matches!(result, Err(MacLookupError::NotFound(_))));
+    }
+
+    #[test]
+    fn get_naming_mac_address_by_options() {
+        let sysfs = Sysfs::new(std::path::Path::new("./tests/unit_test_data/sysfs"));
+        let current: HwAddress = "52:54:00:00:00:01".parse().unwrap();
+        let permanent: HwAddress = "00:1b:44:11:3a:b7".parse().unwrap();
+        let ifcfgs = [IfcfgFile::parse(
+            std::path::Path::new("ifcfg-lan0"),
+            "DEVICE=lan0\nHWADDR=00:1b:44:11:3a:b7\nMACADDR=52:54:00:00:00:01\n",
+        )];
+        let naming = |options: &Options, name: &str| {
+            get_naming_mac_address(
+                &sysfs,
+                options,
+                name,
+                current.clone(),
+                MacOrigin::Current,
+                &ifcfgs,
+            )
+        };
+
+        assert_eq!(
+            naming(&Options::default(), "enp0s31f6"),
+            (current.clone(), MacOrigin::Current)
+        );
+
+        let mac_source = Options {
+            mac_source: MacSource::Permanent,
+            ..Options::default()
+        };
+        assert_eq!(
+            naming(&mac_source, "enp0s31f6"),
+            (permanent.clone(), MacOrigin::Permanent)
+        );
+        assert_eq!(
+            naming(&mac_source, "tun0"),
+            (current.clone(), MacOrigin::Current)
+        );
+
+        let macaddr_mode = Options {
+            macaddr_mode: MacaddrMode::Permanent,
+            ..Options::default()
+        };
+        assert_eq!(
+            naming(&macaddr_mode, "enp0s31f6"),
+            (permanent, MacOrigin::Permanent)
+        );
+        assert_eq!(naming(&macaddr_mode, "tun0"), (current, MacOrigin::Current));
+    }
+
+    #[test]
+    fn get_permanent_mac_address_from_sysfs_root() {
+        let sysfs = Sysfs::new(std::path::Path::new("./tests/unit_test_data/sysfs"));
+
+        assert_eq!(
+            get_permanent_mac_address(&sysfs, "enp0s31f6").unwrap(),
+            "00:1b:44:11:3a:b7".parse().ok()
+        );
+        /* No perm_addr and no ioctl, host may have interface of the same name */
+        assert!(matches!(
+            get_permanent_mac_address(&sysfs, "tun0"),
+            Ok(None)
+        ));
+        assert!(matches!(
+            get_permanent_mac_address(&sysfs, "should-fail0"),
+            Err(MacLookupError::NotFound(_))
+        ));
     }
 }
@@ -0,0 +1,532 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use lazy_static::lazy_static;
+use log::*;
+use regex::Regex;
+
+use ifcfg_devname::exit::ExitCode;
+use ifcfg_devname::options::Options;
+use ifcfg_devname::parser::{FileKind, ParseError};
+use ifcfg_devname::resolver;
+use ifcfg_devname::{IfcfgFile, InvalidNameReason};
+
+use crate::scanner::{self, ScanError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /* Naming works, but probably not the way it was meant to */
+    Warning,
+    /* Interface will be named wrongly or not at all */
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/* Kinds of findings, their codes are stable so pipelines can allow or grep for them */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Code {
+    /* More ifcfg files with the same HWADDR disagree on DEVICE */
+    ConflictingHwaddr,
+    /* File can't be read */
+    UnreadableFile,
+    /* DEVICE is refused by kernel */
+    InvalidDevice,
+    /* DEVICE is longer than kernel allows, other tools silently truncate it */
+    TruncatedDevice,
+    /* HWADDR can't be parsed */
+    InvalidHwaddr,
+    /* More ifcfg files with the same HWADDR agree on DEVICE */
+    DuplicateHwaddr,
+    /* DEVICE looks like name assigned by kernel (eth0, etc.) */
+    KernelName,
+    /* HWADDR is set, but DEVICE isn't */
+    NoDevice,
+    /* Name of scanned file looks like backup */
+    BackupFile,
+    /* Alias or range file sets HWADDR of interface file, but DEVICE of other interface */
+    HijackingAlias,
+}
+
+impl Code {
+    pub fn id(self) -> &'static str {
+        match self {
+            Code::ConflictingHwaddr => "E001",
+            Code::UnreadableFile => "E002",
+            Code::InvalidDevice => "E003",
+            Code::TruncatedDevice => "E004",
+            Code::InvalidHwaddr => "E005",
+            Code::DuplicateHwaddr => "W001",
+            Code::KernelName => "W002",
+            Code::NoDevice => "W003",
+            Code::BackupFile => "W004",
+            Code::HijackingAlias => "W005",
+        }
+    }
+
+    pub fn severity(self) -> Severity {
+        match self {
+            Code::ConflictingHwaddr
+            | Code::UnreadableFile
+            | Code::InvalidDevice
+            | Code::TruncatedDevice
+            | Code::InvalidHwaddr => Severity::Error,
+            Code::DuplicateHwaddr
+            | Code::KernelName
+            | Code::NoDevice
+            | Code::BackupFile
+            | Code::HijackingAlias => Severity::Warning,
+        }
+    }
+}
+
+/* Single issue found in ifcfg file, line is missing when it concerns the whole file */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub code: Code,
+    pub path: PathBuf,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl Finding {
+    fn new(code: Code, ifcfg: &IfcfgFile, key: Option<&str>, message: String) -> Finding {
+        Finding {
+            code,
+            path: ifcfg.path().to_path_buf(),
+            line: key.and_then(|key| ifcfg.entry(key)).map(|entry| entry.line),
+            message,
+        }
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.path.display())?;
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+        }
+
+        write!(
+            f,
+            ": {}[{}]: {}",
+            self.code.severity(),
+            self.code.id(),
+            self.message
+        )
+    }
+}
+
+/* Audit ifcfg files the same way they would be scanned for naming, any finding fails */
+pub fn run(config_dir: &Path, options: &Options) -> Result<ExitCode, Box<dyn error::Error>> {
+    let paths = match scanner::config_dir(config_dir, &options.ignored_suffixes) {
+        Ok(val) => val,
+        Err(ScanError::NoMatches(_)) => vec![],
+        Err(err) => {
+            error!("Fail to get list of ifcfg files: {}", err);
+            return Ok(ExitCode::from(&err));
+        }
+    };
+
+    let findings = lint(&paths);
+
+    for finding in &findings {
+        println!("{}", finding);
+    }
+
+    if findings.is_empty() {
+        info!("No issues found in {} ifcfg files", paths.len());
+        Ok(ExitCode::Found)
+    } else {
+        info!(
+            "Found {} issues in {} ifcfg files",
+            findings.len(),
+            paths.len()
+        );
+        Ok(ExitCode::ConfigError)
+    }
+}
+
+pub fn lint(paths: &[PathBuf]) -> Vec<Finding> {
+    let mut findings = vec![];
+    let mut ifcfgs = vec![];
+
+    for path in paths {
+        match IfcfgFile::open(path) {
+            Ok(ifcfg) => ifcfgs.push(ifcfg),
+            Err(err) => findings.push(Finding {
+                code: Code::UnreadableFile,
+                path: path.clone(),
+                line: None,
+                message: err.to_string(),
+            }),
+        }
+    }
+
+    findings.extend(lint_files(&ifcfgs));
+    findings
+}
+
+fn lint_files(ifcfgs: &[IfcfgFile]) -> Vec<Finding> {
+    let mut findings = vec![];
+
+    for (index, ifcfg) in ifcfgs.iter().enumerate() {
+        if let Some(pattern) = backup_pattern(ifcfg.path()) {
+            findings.push(Finding::new(
+                Code::BackupFile,
+                ifcfg,
+                None,
+                format!(
+                    "file name ends with '{}' like backups do, but it is scanned as ifcfg file",
+                    pattern
+                ),
+            ));
+        }
+
+        /* Alias and range files are never used for naming, only their DEVICE pointing elsewhere is suspicious */
+        if ifcfg.kind() != FileKind::Interface {
+            findings.extend(lint_alias(ifcfg, ifcfgs));
+            continue;
+        }
+
+        let device = match ifcfg.device() {
+            Ok(device) => device,
+            Err(ParseError::InvalidDeviceName {
+                name,
+                reason: InvalidNameReason::TooLong(_),
+                ..
+            }) => {
+                findings.push(Finding::new(
+                    Code::TruncatedDevice,
+                    ifcfg,
+                    Some("DEVICE"),
+                    format!(
+                        "DEVICE '{}' is too long, it isn't used although other tools may truncate it to '{}'",
+                        name,
+                        truncate(&name)
+                    ),
+                ));
+                None
+            }
+            Err(err) => {
+                findings.push(Finding::new(
+                    Code::InvalidDevice,
+                    ifcfg,
+                    Some("DEVICE"),
+                    describe(&err),
+                ));
+                None
+            }
+        };
+
+        /* Kernel names are fine in files not renaming anything */
+        let renames = !matches!(ifcfg.value("HWADDR"), Ok(None));
+
+        if let Some(device) = device {
+            if renames && ifcfg_devname::is_like_kernel_name(device) {
+                findings.push(Finding::new(
+                    Code::KernelName,
+                    ifcfg,
+                    Some("DEVICE"),
+                    format!(
+                        "DEVICE '{}' looks like name assigned by kernel, it may clash with other interface",
+                        device
+                    ),
+                ));
+            }
+        }
+
+        let hwaddr = match ifcfg.hwaddr() {
+            Ok(Some(hwaddr)) => hwaddr,
+            Ok(None) => continue,
+            Err(err) => {
+                findings.push(Finding::new(
+                    Code::InvalidHwaddr,
+                    ifcfg,
+                    Some("HWADDR"),
+                    describe(&err),
+                ));
+                continue;
+            }
+        };
+
+        /* DEVICE that can't be unescaped is already reported as invalid */
+        if matches!(ifcfg.value("DEVICE"), Ok(None)) {
+            findings.push(Finding::new(
+                Code::NoDevice,
+                ifcfg,
+                Some("HWADDR"),
+                format!("HWADDR '{}' is set, but DEVICE isn't", hwaddr),
+            ));
+        }
+
+        /* Each pair of files is reported once, at the later one */
+        let previous = ifcfgs[..index].iter().find(|other| {
+            other.kind() == FileKind::Interface
+                && matches!(other.hwaddr(), Ok(Some(other)) if other.matches(&hwaddr))
+        });
+
+        if let Some(previous) = previous {
+            /* Files without usable DEVICE never agree on the name */
+            let (code, with) = match (previous.device().ok().flatten(), device) {
+                (Some(previous_device), Some(device)) if previous_device == device => (
+                    Code::DuplicateHwaddr,
+                    format!("also with DEVICE '{}'", previous_device),
+                ),
+                (Some(previous_device), _) => (
+                    Code::ConflictingHwaddr,
+                    format!("but with DEVICE '{}'", previous_device),
+                ),
+                (None, _) => (
+                    Code::ConflictingHwaddr,
+                    "but without usable DEVICE".to_string(),
+                ),
+            };
+
+            findings.push(Finding::new(
+                code,
+                ifcfg,
+                Some("HWADDR"),
+                format!(
+                    "HWADDR '{}' is already set in '{}', {}",
+                    hwaddr,
+                    previous.path().display(),
+                    with
+                ),
+            ));
+        }
+    }
+
+    findings
+}
+
+fn lint_alias(ifcfg: &IfcfgFile, ifcfgs: &[IfcfgFile]) -> Option<Finding> {
+    let hijack = resolver::hijack(ifcfg, ifcfgs)?;
+
+    Some(Finding::new(
+        Code::HijackingAlias,
+        ifcfg,
+        Some("DEVICE"),
+        format!(
+            "{} file sets HWADDR '{}' of '{}', but DEVICE '{}' instead of '{}', it isn't used for naming",
+            ifcfg.kind(),
+            hijack.hwaddr,
+            hijack.owner.path().display(),
+            hijack.device,
+            hijack.owner_device
+        ),
+    ))
+}
+
+/* Backups named in ways not covered by ignored suffixes, e.g. ifcfg-eth0.backup or ifcfg-eth0-20240101 */
+fn backup_pattern(path: &Path) -> Option<String> {
+    lazy_static! {
+        /* Look for suffix of file name typical for backups
+         * regex: (?i)([ ._-](bak|backup|bkp|old|orig|save|sav|copy|tmp|dist|disabled)\d*|[._-]\d{8,}|#)$
+         * [ ._-](...)\d* - separator following with common backup word and optional number
+         * [._-]\d{8,} - separator following with date or timestamp
+         * # - autosave of Emacs
+         * example: ifcfg-eth0.backup1 | ifcfg-eth0-20240101 | ifcfg-eth0
+         *                    ^^^^^^^^            ^^^^^^^^^          ~~~~
+         *                     MATCH               MATCH           NO MATCH */
+        static ref REGEX_BACKUP: Regex = Regex::new(
+            r"(?i)([ ._-](bak|backup|bkp|old|orig|save|sav|copy|tmp|dist|disabled)\d*|[._-]\d{8,}|#)$"
+        )
+        .unwrap();
+    }
+
+    let file_name = path.file_name()?.to_string_lossy();
+
+    REGEX_BACKUP
+        .find(&file_name)
+        .map(|suffix| suffix.as_str().to_owned())
+}
+
+/* Name the way it would be cut to fit IFNAMSIZ */
+fn truncate(name: &str) -> &str {
+    let mut end = ifcfg_devname::IFNAMSIZ - 1;
+    while !name.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    &name[..end]
+}
+
+/* Path and line are already part of the finding */
+fn describe(err: &ParseError) -> String {
+    match err {
+        ParseError::Io { source, .. } => format!("fail to read file: {}", source),
+        ParseError::InvalidValue { key, source, .. } => {
+            format!("invalid value of {}: {}", key, source)
+        }
+        ParseError::InvalidDeviceName { name, reason, .. } => {
+            format!("DEVICE '{}' is refused by kernel: {}", name, reason)
+        }
+        ParseError::InvalidHwAddress { key, reason, .. } => {
+            format!("invalid hw address in {}: {}", key, reason)
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod should {
+    use super::*;
+
+    fn codes(findings: &[Finding]) -> Vec<&'static str> {
+        findings.iter().map(|finding| finding.code.id()).collect()
+    }
+
+    #[test]
+    fn lint_ifcfg_files() {
+        let ifcfgs = vec![
+            IfcfgFile::parse(
+                Path::new("ifcfg-a"),
+                "DEVICE=lan0\nHWADDR=AA:BB:CC:DD:EE:01\n",
+            ),
+            IfcfgFile::parse(
+                Path::new("ifcfg-b"),
+                "DEVICE=lan1\nHWADDR=AA:BB:CC:DD:EE:01\n",
+            ),
+            IfcfgFile::parse(
+                Path::new("ifcfg-c.backup"),
+                "DEVICE=lan0\nHWADDR=aa-bb-cc-dd-ee-01\n",
+            ),
+            IfcfgFile::parse(
+                Path::new("ifcfg-d"),
+                "DEVICE=eth3\nHWADDR=AA:BB:CC:DD:EE:XY\n",
+            ),
+            IfcfgFile::parse(
+                Path::new("ifcfg-e"),
+                "HWADDR=AA:BB:CC:DD:EE:05\nDEVICE=storage-frontend0\n",
+            ),
+            IfcfgFile::parse(Path::new("ifcfg-f"), "HWADDR=AA:BB:CC:DD:EE:06\n"),
+            IfcfgFile::parse(
+                Path::new("ifcfg-a:1"),
+                "DEVICE=lan0:1\nHWADDR=AA:BB:CC:DD:EE:01\n",
+            ),
+        ];
+
+        let findings = lint_files(&ifcfgs);
+
+        assert_eq!(
+            codes(&findings),
+            vec!["E001", "W004", "W001", "W002", "E005", "E004", "W003"]
+        );
+        assert_eq!(
+            findings[0].to_string(),
+            "ifcfg-b:2: error[E001]: HWADDR 'aa:bb:cc:dd:ee:01' is already set in 'ifcfg-a', but with DEVICE 'lan0'"
+        );
+        assert_eq!(findings[3].line, Some(1));
+        assert_eq!(
+            findings[5].message,
+            "DEVICE 'storage-frontend0' is too long, it isn't used although other tools may truncate it to 'storage-fronten'"
+        );
+        assert_eq!(
+            findings[6].to_string(),
+            "ifcfg-f:1: warning[W003]: HWADDR 'aa:bb:cc:dd:ee:06' is set, but DEVICE isn't"
+        );
+    }
+
+    #[test]
+    fn lint_duplicate_hwaddr_without_device() {
+        let ifcfgs = vec![
+            IfcfgFile::parse(Path::new("ifcfg-a"), "HWADDR=AA:BB:CC:DD:EE:01\n"),
+            IfcfgFile::parse(Path::new("ifcfg-b"), "HWADDR=AA:BB:CC:DD:EE:01\n"),
+            IfcfgFile::parse(
+                Path::new("ifcfg-c"),
+                "DEVICE=lan0\nHWADDR=AA:BB:CC:DD:EE:01\n",
+            ),
+        ];
+
+        let findings = lint_files(&ifcfgs);
+
+        assert_eq!(codes(&findings), vec!["W003", "W003", "E001", "E001"]);
+        assert_eq!(
+            findings[2].message,
+            "HWADDR 'aa:bb:cc:dd:ee:01' is already set in 'ifcfg-a', but without usable DEVICE"
+        );
+    }
+
+    #[test]
+    fn not_lint_kernel_name_without_hwaddr() {
+        let ifcfgs = vec![IfcfgFile::parse(Path::new("ifcfg-eth0"), "DEVICE=eth0\n")];
+
+        assert!(lint_files(&ifcfgs).is_empty());
+    }
+
+    #[test]
+    fn lint_unescapable_device() {
+        let ifcfgs = vec![IfcfgFile::parse(
+            Path::new("ifcfg-lan0"),
+            "DEVICE=\"lan0\nHWADDR=AA:BB:CC:DD:EE:01\n",
+        )];
+
+        let findings = lint_files(&ifcfgs);
+
+        assert_eq!(codes(&findings), vec!["E003"]);
+        assert_eq!(findings[0].line, Some(1));
+    }
+
+    #[test]
+    fn lint_alias_and_range_files() {
+        let ifcfgs = vec![
+            IfcfgFile::parse(
+                Path::new("ifcfg-lan0"),
+                "DEVICE=lan0\nHWADDR=AA:BB:CC:DD:EE:01\n",
+            ),
+            IfcfgFile::parse(
+                Path::new("ifcfg-lan0:1"),
+                "DEVICE=lan0:1\nHWADDR=AA:BB:CC:DD:EE:01\n",
+            ),
+            IfcfgFile::parse(
+                Path::new("ifcfg-lan0:2"),
+                "DEVICE=lan3\nHWADDR=AA:BB:CC:DD:EE:01\n",
+            ),
+            IfcfgFile::parse(
+                Path::new("ifcfg-lan0-range0"),
+                "DEVICE=lan4\nHWADDR=aa-bb-cc-dd-ee-01\n",
+            ),
+            IfcfgFile::parse(
+                Path::new("ifcfg-lan9:1"),
+                "DEVICE=lan9\nHWADDR=AA:BB:CC:DD:EE:09\n",
+            ),
+        ];
+
+        let findings = lint_files(&ifcfgs);
+
+        assert_eq!(codes(&findings), vec!["W005", "W005"]);
+        assert_eq!(
+            findings[0].to_string(),
+            "ifcfg-lan0:2:1: warning[W005]: alias file sets HWADDR 'aa:bb:cc:dd:ee:01' of 'ifcfg-lan0', but DEVICE 'lan3' instead of 'lan0', it isn't used for naming"
+        );
+        assert_eq!(findings[1].path, Path::new("ifcfg-lan0-range0"));
+    }
+
+    #[test]
+    fn detect_backup_file_names() {
+        assert_eq!(
+            backup_pattern(Path::new("ifcfg-eth0.backup")).as_deref(),
+            Some(".backup")
+        );
+        assert_eq!(
+            backup_pattern(Path::new("ifcfg-eth0-20240101")).as_deref(),
+            Some("-20240101")
+        );
+        assert_eq!(
+            backup_pattern(Path::new("ifcfg-eth0 copy")).as_deref(),
+            Some(" copy")
+        );
+        assert_eq!(backup_pattern(Path::new("ifcfg-eth0")), None);
+        assert_eq!(backup_pattern(Path::new("ifcfg-lan0")), None);
+    }
+}
@@ -0,0 +1,132 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::error;
+use std::path::Path;
+
+use log::*;
+
+use ifcfg_devname::exit::ExitCode;
+use ifcfg_devname::options::Options;
+use ifcfg_devname::report::Mapping;
+
+use crate::cli::ListFormat;
+use crate::scanner::{self, ScanError};
+
+/* Print every hw address to name mapping, config management can diff against it */
+pub fn run(
+    config_dir: &Path,
+    options: &Options,
+    format: ListFormat,
+) -> Result<ExitCode, Box<dyn error::Error>> {
+    let ifcfgs = match scanner::load(config_dir, &options.ignored_suffixes) {
+        Ok(val) => val,
+        Err(ScanError::NoMatches(_)) => vec![],
+        Err(err) => {
+            error!("Fail to get list of ifcfg files: {}", err);
+            return Ok(ExitCode::from(&err));
+        }
+    };
+
+    print(&Mapping::collect(&ifcfgs), format)?;
+
+    Ok(ExitCode::Found)
+}
+
+pub fn print(mappings: &[Mapping], format: ListFormat) -> Result<(), serde_json::Error> {
+    const HEADER: [&str; 5] = ["HWADDR", "DEVICE", "FILE", "WARNINGS", "REASON"];
+
+    let rows: Vec<[String; 5]> = mappings.iter().map(columns).collect();
+
+    match format {
+        ListFormat::Table => print_table(HEADER, &rows),
+        ListFormat::Json => println!("{}", serde_json::to_string_pretty(mappings)?),
+        ListFormat::Csv => print_csv(HEADER, &rows),
+    }
+
+    Ok(())
+}
+
+fn columns(mapping: &Mapping) -> [String; 5] {
+    let warnings: Vec<String> = mapping
+        .warnings
+        .iter()
+        .map(|warning| warning.to_string())
+        .collect();
+
+    [
+        mapping.hwaddr.clone().unwrap_or_default(),
+        mapping.device.clone().unwrap_or_default(),
+        mapping.file.to_string_lossy().into_owned(),
+        warnings.join(","),
+        mapping.reason.clone().unwrap_or_default(),
+    ]
+}
+
+/* Aligned columns, empty cells are printed as "-" */
+pub fn print_table<const N: usize>(header: [&str; N], rows: &[[String; N]]) {
+    let rows: Vec<[&str; N]> = rows
+        .iter()
+        .map(|row| {
+            row.each_ref().map(|column| {
+                if column.is_empty() {
+                    "-"
+                } else {
+                    column.as_str()
+                }
+            })
+        })
+        .collect();
+
+    let mut widths = header.map(str::len);
+    for row in &rows {
+        for (width, column) in widths.iter_mut().zip(row) {
+            *width = (*width).max(column.chars().count());
+        }
+    }
+
+    for row in std::iter::once(&header).chain(&rows) {
+        let mut line = String::new();
+        for (index, (column, width)) in row.iter().zip(widths).enumerate() {
+            if index + 1 == N {
+                line.push_str(column);
+            } else {
+                line.push_str(&format!("{:<width$}  ", column, width = width));
+            }
+        }
+        println!("{}", line);
+    }
+}
+
+/* Header is printed in lowercase, the same as keys of JSON output */
+pub fn print_csv<const N: usize>(header: [&str; N], rows: &[[String; N]]) {
+    println!("{}", header.map(str::to_lowercase).join(","));
+
+    for row in rows {
+        let row: Vec<String> = row.iter().map(|column| csv_field(column)).collect();
+        println!("{}", row.join(","));
+    }
+}
+
+/* Quote field according to RFC 4180 when it contains separator, quote or line break */
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+#[cfg(test)]
+pub mod should {
+    use super::*;
+
+    #[test]
+    fn quote_csv_field() {
+        assert_eq!(csv_field("lan0"), "lan0");
+        assert_eq!(
+            csv_field("conflict,kernel-name"),
+            "\"conflict,kernel-name\""
+        );
+        assert_eq!(csv_field("my \"lan\""), "\"my \"\"lan\"\"\"");
+    }
+}
@@ -3,11 +3,12 @@
 use log::LevelFilter;
 use syslog::{BasicLogger, Error, Facility, Formatter3164, Logger, LoggerBackend};
 
-pub fn init() {
+/* Verbosity shifts default log level, positive values make logging more verbose */
+pub fn init(verbosity: i8) {
     if let Ok(connection) = connect_syslog() {
-        setup_syslog(connection);
+        setup_syslog(connection, verbosity);
     } else {
-        setup_stderr_logging();
+        setup_stderr_logging(verbosity);
     }
 }
 
@@ -22,14 +23,30 @@ fn connect_syslog() -> Result<Logger<LoggerBackend, Formatter3164>, Error> {
     syslog::unix(formatter)
 }
 
-fn setup_syslog(logger: Logger<LoggerBackend, Formatter3164>) {
+fn setup_syslog(logger: Logger<LoggerBackend, Formatter3164>, verbosity: i8) {
+    const LEVELS: [LevelFilter; 6] = [
+        LevelFilter::Off,
+        LevelFilter::Error,
+        LevelFilter::Warn,
+        LevelFilter::Info,
+        LevelFilter::Debug,
+        LevelFilter::Trace,
+    ];
+    let index = (LevelFilter::Info as i8 + verbosity).clamp(0, LEVELS.len() as i8 - 1);
+
     log::set_boxed_logger(Box::new(BasicLogger::new(logger)))
-        .map(|()| log::set_max_level(LevelFilter::Info))
+        .map(|()| log::set_max_level(LEVELS[index as usize]))
         .unwrap();
 }
 
-fn setup_stderr_logging() {
-    stderrlog::new().module(module_path!()).init().unwrap();
+/* Same default level as syslog, stderrlog counts from errors only */
+fn setup_stderr_logging(verbosity: i8) {
+    stderrlog::new()
+        .module(env!("CARGO_CRATE_NAME"))
+        .quiet(verbosity < -2)
+        .verbosity(2i8.saturating_add(verbosity).max(0) as usize)
+        .init()
+        .unwrap();
 }
 
 #[cfg(test)]
@@ -42,7 +59,7 @@ pub mod should {
 
     #[test]
     fn setup_stderr_logger() {
-        setup_stderr_logging();
+        setup_stderr_logging(0);
     }
 
     #[test]
@@ -1,93 +1,387 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use std::env;
 use std::error;
-use std::path::Path;
+use std::str::FromStr;
 
 use log::*;
 
+use ifcfg_devname::exit::ExitCode;
+use ifcfg_devname::naming::{self, PlanStatus};
+use ifcfg_devname::options::Options;
+use ifcfg_devname::parser::ParseError;
+use ifcfg_devname::report::{Collision, MacOrigin, Report, Warning};
+use ifcfg_devname::resolver::{self, Outcome};
+use ifcfg_devname::udev::{self, UdevEvent};
+use ifcfg_devname::{HwAddress, IfcfgFile, MacLookupError, Sysfs};
+
+use cli::{Cli, Command, Format};
+
+mod cli;
+mod explain;
+mod lint;
+mod list;
 mod logger;
-mod parser;
+mod plan;
+mod reverse;
 mod scanner;
 
-enum Args {
-    ConfigDir = 1,
-    Mac,
-    Length,
-}
-
 fn main() -> Result<(), Box<dyn error::Error>> {
-    const ENV: &str = "INTERFACE";
-    const CONFIG_DIR: &str = "/etc/sysconfig/network-scripts";
+    let cli = Cli::parse_args();
+
+    logger::init(cli.verbosity());
 
-    let args: Vec<String> = env::args().collect();
-    let is_test_mode = ifcfg_devname::is_test_mode(&args, Args::Length as usize);
+    if cli.is_legacy() {
+        warn!("Positional arguments <CONFIG_DIR> <HWADDR> are deprecated and will be removed, use --config-dir and --mac instead");
+    }
+
+    let mut options = Options::from_env();
+    if cli.sysfs_root.is_some() {
+        options.sysfs_root = cli.sysfs_root.clone();
+    }
 
-    logger::init();
+    let sysfs = options
+        .sysfs_root
+        .as_deref()
+        .map(Sysfs::new)
+        .unwrap_or_default();
+
+    if let Some(command) = &cli.command {
+        let exit_code = match command {
+            Command::List { format } => list::run(&cli.config_dir, &options, *format)?,
+            Command::Reverse { name, format } => {
+                reverse::run(&cli.config_dir, &options, name, *format)?
+            }
+            Command::Plan { format } => plan::run(&cli.config_dir, &options, &sysfs, *format)?,
+            Command::Lint => lint::run(&cli.config_dir, &options)?,
+        };
 
-    let kernel_interface_name = match env::var_os(ENV) {
-        Some(val) => val.into_string().unwrap(),
+        if exit_code != ExitCode::Found {
+            exit_code.exit();
+        }
+
+        return Ok(());
+    }
+
+    /* Interface given on command line means the program was run by hand, not by udev */
+    let kernel_interface_name = match &cli.interface {
+        Some(name) => name.clone(),
         None => {
-            error!("Fail to obtain environment variable '{}'", ENV);
-            std::process::exit(1)
+            let event = UdevEvent::from_env();
+            if !event.is_relevant() {
+                debug!(
+                    "Nothing to do for '{}' event of '{}' subsystem",
+                    event.action.as_deref().unwrap_or_default(),
+                    event.subsystem.as_deref().unwrap_or_default()
+                );
+                return Ok(());
+            }
+
+            match event.interface_name(&sysfs) {
+                Ok(val) => val,
+                Err(err) => {
+                    error!("{}", err);
+                    ExitCode::from(&err).exit()
+                }
+            }
         }
     };
 
-    let mac_address = match ifcfg_devname::get_mac_address(
-        is_test_mode,
-        &args,
-        Args::Mac as usize,
-        &kernel_interface_name,
-    ) {
+    let mac_address = match &cli.mac {
+        Some(value) => HwAddress::from_str(value)
+            .map_err(|reason| MacLookupError::InvalidAddress(value.clone(), reason)),
+        None => ifcfg_devname::get_mac_address(&sysfs, &kernel_interface_name),
+    };
+
+    let mac_address = match mac_address {
         Ok(val) => val,
-        _ => {
-            error!("Fail to resolve MAC address of '{}'", kernel_interface_name);
-            std::process::exit(1)
+        Err(err) => {
+            error!("Fail to resolve MAC address: {}", err);
+            print_failure(
+                &cli,
+                &options,
+                &kernel_interface_name,
+                None,
+                format!("{}, the interface keeps its name", err),
+                ExitCode::from(&err),
+            )?;
+            ExitCode::from(&err).exit()
         }
     };
 
-    let simple_mac_address = mac_address.to_string().to_lowercase();
-
-    let config_dir = if !is_test_mode {
-        CONFIG_DIR
-    } else {
-        &args[Args::ConfigDir as usize]
+    let mac_origin = match cli.mac {
+        Some(_) => MacOrigin::CommandLine,
+        None => MacOrigin::Current,
     };
 
-    let config_dir_path = Path::new(config_dir);
-    let ifcfg_paths = match scanner::config_dir(config_dir_path) {
-        Some(val) => val,
-        None => {
-            error!(
-                "Fail to get list of ifcfg files from directory '{}'",
-                config_dir
-            );
-            std::process::exit(1)
+    let ifcfgs = match scanner::load(&cli.config_dir, &options.ignored_suffixes) {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Fail to get list of ifcfg files: {}", err);
+            print_failure(
+                &cli,
+                &options,
+                &kernel_interface_name,
+                Some((&mac_address, mac_origin)),
+                format!("{}, the interface keeps its name", err),
+                ExitCode::from(&err),
+            )?;
+            ExitCode::from(&err).exit()
         }
     };
 
-    let mut device_config_name = String::new();
+    let (mac_address, mac_origin) = ifcfg_devname::get_naming_mac_address(
+        &sysfs,
+        &options,
+        &kernel_interface_name,
+        mac_address,
+        mac_origin,
+        &ifcfgs,
+    );
+
+    /* Random address may come from driver that failed to read EEPROM, stolen one belongs to other device
+     * --mac overrides only the address, the interface itself is still checked when sysfs knows it */
+    if mac_origin != MacOrigin::Permanent {
+        let addr_assign_type = sysfs
+            .interface(&kernel_interface_name)
+            .ok()
+            .and_then(|interface| interface.addr_assign_type);
 
-    for path in ifcfg_paths {
-        let config_file_path: &Path = Path::new(&path);
+        match addr_assign_type {
+            Some(addr_assign_type)
+                if !options.addr_assign_policy.trusts(Some(addr_assign_type)) =>
+            {
+                warn!(
+                    "MAC address '{}' of '{}' is {}, refusing to use it for naming",
+                    mac_address, kernel_interface_name, addr_assign_type
+                );
 
-        match parser::config_file(config_file_path, &simple_mac_address) {
-            Ok(Some(name)) => {
-                if ifcfg_devname::is_like_kernel_name(&name) {
-                    warn!("Don't use kernel names (eth0, etc.) as new names for network devices! Used name: '{}'", name);
-                }
-                device_config_name = name;
-                break;
+                print_failure(
+                    &cli,
+                    &options,
+                    &kernel_interface_name,
+                    Some((&mac_address, mac_origin)),
+                    format!(
+                        "MAC address is {} (see IFCFG_DEVNAME_ADDR_ASSIGN_TYPES), the interface keeps its name",
+                        addr_assign_type
+                    ),
+                    ExitCode::NoMatch,
+                )?;
+
+                ExitCode::NoMatch.exit();
             }
+            _ => (),
+        }
+    }
+
+    let resolution = resolver::resolve(&ifcfgs, &mac_address);
+
+    for candidate in &resolution.candidates {
+        match &candidate.outcome {
+            Outcome::OnlyMacaddr => info!(
+                "Skipping '{}': it sets MACADDR but not HWADDR",
+                candidate.ifcfg.path().display()
+            ),
+            /* Files that can't be read are already reported by scanner */
+            Outcome::Error(ParseError::Io { .. }) => continue,
+            Outcome::Error(err) => error!("{}", err),
+            Outcome::Excluded {
+                kind,
+                hijacked: Some(name),
+            } => warn!(
+                "'{}' is {} file, its DEVICE '{}' matching MAC address '{}' is ignored",
+                candidate.ifcfg.path().display(),
+                kind,
+                name,
+                mac_address
+            ),
             _ => continue,
         }
     }
 
-    if !device_config_name.is_empty() {
-        println!("{}", device_config_name);
-        Ok(())
-    } else {
-        error!("Device name or MAC address weren't found in ifcfg files.");
-        std::process::exit(1);
+    let mut warnings = vec![];
+
+    if let Some(conflict) = resolution.conflict() {
+        warn!("{}, using precedence '{}'", conflict, options.precedence);
+        warnings.push(Warning::Conflict);
+    }
+
+    let (selected, mut exit_code) = match resolution.select(options.precedence) {
+        Ok(Some((ifcfg, name))) => {
+            if ifcfg_devname::is_like_kernel_name(name) {
+                warn!("Don't use kernel names (eth0, etc.) as new names for network devices! Used name: '{}'", name);
+                warnings.push(Warning::KernelName);
+            }
+            debug!("Using DEVICE from '{}'", ifcfg.path().display());
+            (Some((ifcfg, name)), ExitCode::Found)
+        }
+        /* Matching ifcfg file with DEVICE kernel refuses is already reported as error */
+        Ok(None)
+            if resolution.candidates.iter().any(|candidate| {
+                matches!(
+                    candidate.outcome,
+                    Outcome::Error(ParseError::InvalidDeviceName { .. })
+                )
+            }) =>
+        {
+            (None, ExitCode::InvalidName)
+        }
+        Ok(None) => {
+            error!("Device name or MAC address weren't found in ifcfg files.");
+            (None, ExitCode::NoMatch)
+        }
+        Err(conflict) => {
+            error!("Refusing to rename: {}", conflict);
+            (None, ExitCode::ConfigError)
+        }
+    };
+
+    /* Kernel refuses to use name of other interface, udev would leave interfaces half-renamed */
+    let collision = match selected {
+        Some((_, name)) => ifcfg_devname::find_name_owner(&sysfs, name, &kernel_interface_name)
+            .map(|owner| {
+                let owner_step = naming::step(&sysfs, &options, &owner.name, &ifcfgs);
+
+                Collision {
+                    interface: owner.name,
+                    mac_address: owner.address.map(|address| address.to_string()),
+                    eventually: owner_step
+                        .name
+                        .filter(|_| owner_step.status == PlanStatus::Rename),
+                }
+            }),
+        _ => None,
+    };
+
+    if let (Some(collision), Some((_, name))) = (&collision, selected) {
+        error!(
+            "Can't rename '{}' to '{}': {}",
+            kernel_interface_name, name, collision
+        );
+        warnings.push(Warning::NameTaken);
+        exit_code = ExitCode::NameTaken;
+    }
+
+    match (cli.format(), selected) {
+        (Format::Plain, Some((_, name))) if collision.is_none() => println!("{}", name),
+        (Format::Export, Some((ifcfg, name))) => {
+            print_export(name, ifcfg, &mac_address, &warnings, collision.as_ref())
+        }
+        (Format::Json | Format::Explain, _) => {
+            let mut report = Report::new(
+                &kernel_interface_name,
+                &mac_address,
+                mac_origin,
+                &resolution,
+                selected,
+                &warnings,
+                exit_code,
+            );
+            report.ignored = scanner::ignored_files(&cli.config_dir, &options.ignored_suffixes);
+            report.collision = collision.clone();
+            print_report(cli.format(), &report)?;
+        }
+        _ => (),
+    }
+
+    /* udev imports properties only from programs that succeed, rules need the hint to retry later */
+    let eventually = collision.is_some_and(|collision| collision.eventually.is_some());
+    if cli.format() == Format::Export && eventually {
+        return Ok(());
+    }
+
+    if exit_code != ExitCode::Found {
+        exit_code.exit();
+    }
+
+    Ok(())
+}
+
+fn print_report(format: Format, report: &Report) -> Result<(), serde_json::Error> {
+    match format {
+        Format::Explain => explain::print(report),
+        _ => println!("{}", serde_json::to_string_pretty(report)?),
+    }
+
+    Ok(())
+}
+
+/* Resolution stopped early, --format json and --explain still tell which step failed and why */
+fn print_failure(
+    cli: &Cli,
+    options: &Options,
+    interface: &str,
+    mac_address: Option<(&HwAddress, MacOrigin)>,
+    decision: String,
+    exit_code: ExitCode,
+) -> Result<(), serde_json::Error> {
+    if !matches!(cli.format(), Format::Json | Format::Explain) {
+        return Ok(());
+    }
+
+    let mut report = Report::failed(interface, mac_address, decision, exit_code);
+    report.ignored = scanner::ignored_files(&cli.config_dir, &options.ignored_suffixes);
+
+    print_report(cli.format(), &report)
+}
+
+/* Properties for IMPORT{program}, rules can set NAME conditionally and keep provenance in udev database
+ * NAME is left out when the name belongs to other interface, EVENTUALLY tells it will be free later */
+fn print_export(
+    name: &str,
+    ifcfg: &IfcfgFile,
+    mac_address: &HwAddress,
+    warnings: &[Warning],
+    collision: Option<&Collision>,
+) {
+    let properties = [
+        (
+            "NAME",
+            collision.map_or_else(|| name.to_owned(), |_| String::new()),
+        ),
+        ("FILE", ifcfg.path().to_string_lossy().into_owned()),
+        ("MAC", mac_address.to_string()),
+        ("MATCH", String::from("hwaddr")),
+        (
+            "WARNING",
+            warnings
+                .iter()
+                .map(|warning| warning.to_string())
+                .collect::<Vec<_>>()
+                .join(","),
+        ),
+        (
+            "COLLISION",
+            collision
+                .map(|collision| collision.interface.clone())
+                .unwrap_or_default(),
+        ),
+        (
+            "COLLISION_MAC",
+            collision
+                .and_then(|collision| collision.mac_address.clone())
+                .unwrap_or_default(),
+        ),
+        (
+            "EVENTUALLY",
+            collision
+                .filter(|collision| collision.eventually.is_some())
+                .map(|_| name.to_owned())
+                .unwrap_or_default(),
+        ),
+    ];
+
+    for (key, value) in properties {
+        if value.is_empty() {
+            continue;
+        }
+
+        match udev::quote_property(&value) {
+            Some(quoted) => println!("IFCFG_DEVNAME_{}={}", key, quoted),
+            None => warn!(
+                "Property IFCFG_DEVNAME_{} isn't exported, udev can't import value '{}'",
+                key,
+                value.escape_debug()
+            ),
+        }
     }
 }
@@ -0,0 +1,179 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::fmt;
+use std::path::PathBuf;
+
+use log::*;
+use serde::Serialize;
+
+use crate::options::Options;
+use crate::parser::ParseError;
+use crate::report::MacOrigin;
+use crate::resolver::{self, Outcome};
+use crate::{IfcfgFile, Sysfs};
+
+/* What would happen to single interface, see PlanStep */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PlanStatus {
+    /* Interface would be renamed */
+    Rename,
+    /* Interface already has the name from ifcfg file */
+    Named,
+    /* More interfaces would receive the same name, only one rename would succeed */
+    Collision,
+    /* No ifcfg file matches, interface keeps its name */
+    Unmatched,
+    /* Interface has no link-layer address */
+    NoAddress,
+    /* hw address isn't trusted according to IFCFG_DEVNAME_ADDR_ASSIGN_TYPES */
+    Untrusted,
+    /* Matching ifcfg file sets DEVICE kernel refuses */
+    InvalidName,
+    /* Matching ifcfg files disagree on DEVICE and precedence is refuse */
+    Conflict,
+    /* Interface can't be inspected */
+    Error,
+}
+
+impl fmt::Display for PlanStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PlanStatus::Rename => write!(f, "rename"),
+            PlanStatus::Named => write!(f, "named"),
+            PlanStatus::Collision => write!(f, "collision"),
+            PlanStatus::Unmatched => write!(f, "unmatched"),
+            PlanStatus::NoAddress => write!(f, "no-address"),
+            PlanStatus::Untrusted => write!(f, "untrusted"),
+            PlanStatus::InvalidName => write!(f, "invalid-name"),
+            PlanStatus::Conflict => write!(f, "conflict"),
+            PlanStatus::Error => write!(f, "error"),
+        }
+    }
+}
+
+/* Planned name of single interface present on the host */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PlanStep {
+    pub interface: String,
+    pub mac_address: Option<String>,
+    pub mac_origin: Option<MacOrigin>,
+    pub name: Option<String>,
+    pub file: Option<PathBuf>,
+    pub status: PlanStatus,
+}
+
+/* The same steps as naming of single interface by udev takes, used by plan and for hints about name owners */
+pub fn step(sysfs: &Sysfs, options: &Options, name: &str, ifcfgs: &[IfcfgFile]) -> PlanStep {
+    let mut step = PlanStep {
+        interface: name.to_owned(),
+        mac_address: None,
+        mac_origin: None,
+        name: None,
+        file: None,
+        status: PlanStatus::Error,
+    };
+
+    let interface = match sysfs.interface(name) {
+        Ok(val) => val,
+        Err(err) => {
+            warn!("{}", err);
+            return step;
+        }
+    };
+
+    let current = match interface.address {
+        Some(address) => address,
+        None => {
+            step.status = PlanStatus::NoAddress;
+            return step;
+        }
+    };
+
+    let (mac_address, mac_origin) =
+        crate::get_naming_mac_address(sysfs, options, name, current, MacOrigin::Current, ifcfgs);
+    step.mac_address = Some(mac_address.to_string());
+    step.mac_origin = Some(mac_origin);
+
+    if mac_origin != MacOrigin::Permanent
+        && !options
+            .addr_assign_policy
+            .trusts(interface.addr_assign_type)
+    {
+        step.status = PlanStatus::Untrusted;
+        return step;
+    }
+
+    let resolution = resolver::resolve(ifcfgs, &mac_address);
+
+    step.status = match resolution.select(options.precedence) {
+        Ok(Some((ifcfg, target))) => {
+            step.name = Some(target.to_owned());
+            step.file = Some(ifcfg.path().to_path_buf());
+
+            if target == name {
+                PlanStatus::Named
+            } else {
+                PlanStatus::Rename
+            }
+        }
+        Ok(None)
+            if resolution.candidates.iter().any(|candidate| {
+                matches!(
+                    candidate.outcome,
+                    Outcome::Error(ParseError::InvalidDeviceName { .. })
+                )
+            }) =>
+        {
+            PlanStatus::InvalidName
+        }
+        Ok(None) => PlanStatus::Unmatched,
+        Err(conflict) => {
+            warn!("{}", conflict);
+            PlanStatus::Conflict
+        }
+    };
+
+    step
+}
+
+#[cfg(test)]
+pub mod should {
+    use super::*;
+
+    use std::fs;
+    use std::path::Path;
+
+    const TEST_PLAN_DIR: &str = "./tests/unit_test_data/plan";
+
+    #[test]
+    fn step_single_interface() {
+        let plan_dir = Path::new(TEST_PLAN_DIR);
+        let sysfs = Sysfs::new(&plan_dir.join("sysfs"));
+        let ifcfgs: Vec<IfcfgFile> = ["ifcfg-lan0", "ifcfg-lan1"]
+            .iter()
+            .map(|name| {
+                let path = plan_dir.join("ifcfgs").join(name);
+                IfcfgFile::parse(&path, &fs::read_to_string(&path).unwrap())
+            })
+            .collect();
+
+        let rename = step(&sysfs, &Options::default(), "enp1s0", &ifcfgs);
+        assert_eq!(rename.status, PlanStatus::Rename);
+        assert_eq!(rename.name.as_deref(), Some("lan0"));
+        assert_eq!(rename.mac_origin, Some(MacOrigin::Current));
+
+        assert_eq!(
+            step(&sysfs, &Options::default(), "lan1", &ifcfgs).status,
+            PlanStatus::Named
+        );
+        assert_eq!(
+            step(&sysfs, &Options::default(), "lo", &ifcfgs).status,
+            PlanStatus::NoAddress
+        );
+        assert_eq!(
+            step(&sysfs, &Options::default(), "missing0", &ifcfgs).status,
+            PlanStatus::Error
+        );
+    }
+}
@@ -0,0 +1,187 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::env;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use log::*;
+
+use crate::resolver::Precedence;
+use crate::sysfs::AddrAssignType;
+
+/* How to treat network interfaces that have hw address assigned by MACADDR, MacSource::Permanent overrides it */
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MacaddrMode {
+    /* Match HWADDR only against current hw address of network interface */
+    #[default]
+    Current,
+    /* When current hw address equals MACADDR of some ifcfg file, match HWADDR against permanent hw address */
+    Permanent,
+}
+
+impl FromStr for MacaddrMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "current" => Ok(MacaddrMode::Current),
+            "permanent" => Ok(MacaddrMode::Permanent),
+            _ => Err(format!(
+                "unknown MACADDR mode '{}', expected 'current' or 'permanent'",
+                value
+            )),
+        }
+    }
+}
+
+/* Which hw address of network interface is matched against HWADDR */
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MacSource {
+    /* Address the interface currently uses, it may be changed by MACADDR, bonding, teaming, etc. */
+    #[default]
+    Current,
+    /* Burned-in address reported by driver, current address is used when driver doesn't provide it */
+    Permanent,
+}
+
+impl FromStr for MacSource {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "current" => Ok(MacSource::Current),
+            "permanent" => Ok(MacSource::Permanent),
+            _ => Err(format!(
+                "unknown MAC source '{}', expected 'current' or 'permanent'",
+                value
+            )),
+        }
+    }
+}
+
+/* Kinds of hw addresses (addr_assign_type) trusted to name network interfaces */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddrAssignPolicy(Vec<AddrAssignType>);
+
+/* Random address may be generated when driver fails to read EEPROM and stolen one belongs to other device */
+impl Default for AddrAssignPolicy {
+    fn default() -> Self {
+        AddrAssignPolicy(vec![AddrAssignType::Permanent, AddrAssignType::Set])
+    }
+}
+
+impl FromStr for AddrAssignPolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        split_list(value)
+            .iter()
+            .map(|item| item.parse())
+            .collect::<Result<_, _>>()
+            .map(AddrAssignPolicy)
+    }
+}
+
+impl AddrAssignPolicy {
+    /* Address of unknown type is trusted, older kernels and some drivers don't report it */
+    pub fn trusts(&self, addr_assign_type: Option<AddrAssignType>) -> bool {
+        match addr_assign_type {
+            Some(addr_assign_type) => self.0.contains(&addr_assign_type),
+            None => true,
+        }
+    }
+}
+
+/* Tunables of name resolution, udev rules can set them using ENV{IFCFG_DEVNAME_*} */
+#[derive(Debug, Default, Clone)]
+pub struct Options {
+    pub mac_source: MacSource,
+    pub macaddr_mode: MacaddrMode,
+    pub addr_assign_policy: AddrAssignPolicy,
+    pub precedence: Precedence,
+    /* Suffixes of files ignored by scanner on top of built-in list */
+    pub ignored_suffixes: Vec<String>,
+    /* Where sysfs is mounted, tests point it to directory with fake interfaces */
+    pub sysfs_root: Option<PathBuf>,
+}
+
+impl Options {
+    pub fn from_env() -> Options {
+        Options {
+            mac_source: from_env_var("IFCFG_DEVNAME_MAC_SOURCE"),
+            macaddr_mode: from_env_var("IFCFG_DEVNAME_MACADDR_MODE"),
+            addr_assign_policy: from_env_var("IFCFG_DEVNAME_ADDR_ASSIGN_TYPES"),
+            precedence: from_env_var("IFCFG_DEVNAME_PRECEDENCE"),
+            ignored_suffixes: env::var("IFCFG_DEVNAME_IGNORE_SUFFIXES")
+                .map(|value| split_list(&value))
+                .unwrap_or_default(),
+            sysfs_root: env::var_os("IFCFG_DEVNAME_SYSFS_ROOT").map(PathBuf::from),
+        }
+    }
+}
+
+/* Read value of environment variable, fall back to default when it's unset or invalid */
+fn from_env_var<T>(name: &str) -> T
+where
+    T: FromStr<Err = String> + Default,
+{
+    match env::var(name) {
+        Ok(value) => value.parse().unwrap_or_else(|err| {
+            warn!("Ignoring environment variable '{}': {}", name, err);
+            T::default()
+        }),
+        Err(_) => T::default(),
+    }
+}
+
+/* Split list of values separated by commas or whitespaces */
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+#[cfg(test)]
+pub mod should {
+    use super::*;
+
+    #[test]
+    fn parse_macaddr_mode() {
+        assert_eq!("permanent".parse(), Ok(MacaddrMode::Permanent));
+        assert_eq!("current".parse(), Ok(MacaddrMode::Current));
+        assert!("spoofed".parse::<MacaddrMode>().is_err());
+    }
+
+    #[test]
+    fn parse_mac_source() {
+        assert_eq!("permanent".parse(), Ok(MacSource::Permanent));
+        assert_eq!("current".parse(), Ok(MacSource::Current));
+        assert!("burned-in".parse::<MacSource>().is_err());
+    }
+
+    #[test]
+    fn parse_addr_assign_policy() {
+        let default = AddrAssignPolicy::default();
+        let relaxed: AddrAssignPolicy = "permanent, random,set".parse().unwrap();
+
+        assert!(default.trusts(Some(AddrAssignType::Permanent)));
+        assert!(default.trusts(Some(AddrAssignType::Set)));
+        assert!(default.trusts(None));
+        assert!(!default.trusts(Some(AddrAssignType::Random)));
+        assert!(!default.trusts(Some(AddrAssignType::Stolen)));
+        assert!(relaxed.trusts(Some(AddrAssignType::Random)));
+        assert!(!relaxed.trusts(Some(AddrAssignType::Stolen)));
+        assert!("permanent,spoofed".parse::<AddrAssignPolicy>().is_err());
+    }
+
+    #[test]
+    fn split_list_of_values() {
+        assert_eq!(
+            split_list(".tmp, .disabled  .save"),
+            vec![".tmp", ".disabled", ".save"]
+        );
+        assert!(split_list("").is_empty());
+    }
+}
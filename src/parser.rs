@@ -1,73 +1,368 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 use std::error;
-use std::fs::File;
-use std::io::{prelude::*, BufReader};
-use std::path::Path;
-
-use mac_address::MacAddress;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
 
 use lazy_static::lazy_static;
 use regex::Regex;
 
-/* Implement conversion from any type that implements the Error trait into the trait object Box<Error>
- * https://doc.rust-lang.org/std/keyword.dyn.html */
-type Result<T> = std::result::Result<T, Box<dyn error::Error>>;
-
-/* Scan ifcfg files and look for given HWADDR and return DEVICE name */
-pub fn config_file(config_file: &Path, mac_address: &str) -> Result<Option<String>> {
-    let file = File::open(config_file)?;
-    let reader = BufReader::new(file);
-    let mut hwaddr: Option<MacAddress> = None;
-    let mut device: Option<String> = None;
-
-    lazy_static! {
-        /* Look for line that starts with DEVICE= and then store everything else in group
-         * regex: ^\s*DEVICE=(\S[^:]{0,14})
-         * ^\s*DEVICE=(group1) - look for line starting with `DEVICE=` (ignore whitespaces) following with group of characters describing new device name
-         * group1: (\S[^:]{0,14}) - match non-whitespace characters ; minimum 1 and maximum 15 ; do not match `:` character
-         * example: DEVICE=new-devname007
-         *                 ^^^^^^^^^^^^^^
-         *                 new dev name */
-        static ref REGEX_DEVICE: Regex = Regex::new(r"^\s*DEVICE=(\S[^:]{0,14})").unwrap();
-
-        /* Look for line with mac address and store its value in group for later
-         * regex: ^\s*HWADDR=(([0-9A-Fa-f]{2}[:]){5}([0-9A-Fa-f]{2}))
-         * ^\s*HWADDR=(group1) - look for line starting with `HWADDR=` (ignore whitespaces) following with group of characters describing hw address of device
-         * group1: (([0-9A-Fa-f]{2}[:]){5}([0-9A-Fa-f]{2})) - match 48-bit hw address expressed in hexadecimal system ; each of inner 8-bits are separated with `:` character ; case insensitive
-         * example: HWADDR=00:1b:44:11:3A:B7
-         *                 ^^^^^^^^^^^^^^^^^
-         *                 hw address of if */
-        static ref REGEX_HWADDR: Regex = Regex::new(r"^\s*HWADDR=(([0-9A-Fa-f]{2}[:]){5}([0-9A-Fa-f]{2}))").unwrap();
-    }
-
-    /* Read lines of given file and look for DEVICE= and HWADDR= */
-    for line in reader.lines() {
-        let line = line?;
-
-        /* Look for HWADDR= */
-        if REGEX_HWADDR.is_match(&line) {
-            for capture in REGEX_HWADDR.captures_iter(&line) {
-                hwaddr = Some(capture[1].parse()?);
+use crate::hwaddr::Notation;
+use crate::shell::{self, UnescapeError};
+use crate::{HwAddress, InvalidNameReason};
+
+/* Errors related to content of ifcfg file, each of them points to its origin */
+#[derive(Debug)]
+pub enum ParseError {
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    InvalidValue {
+        path: PathBuf,
+        line: usize,
+        key: String,
+        source: UnescapeError,
+    },
+    InvalidDeviceName {
+        path: PathBuf,
+        line: usize,
+        name: String,
+        reason: InvalidNameReason,
+    },
+    InvalidHwAddress {
+        path: PathBuf,
+        line: usize,
+        key: String,
+        reason: String,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Io { path, source } => {
+                write!(f, "{}: fail to read file: {}", path.display(), source)
             }
+            ParseError::InvalidValue {
+                path,
+                line,
+                key,
+                source,
+            } => write!(
+                f,
+                "{}:{}: invalid value of {}: {}",
+                path.display(),
+                line,
+                key,
+                source
+            ),
+            ParseError::InvalidDeviceName {
+                path,
+                line,
+                name,
+                reason,
+            } => write!(
+                f,
+                "{}:{}: invalid DEVICE name '{}': {}",
+                path.display(),
+                line,
+                name,
+                reason
+            ),
+            ParseError::InvalidHwAddress {
+                path,
+                line,
+                key,
+                reason,
+            } => write!(
+                f,
+                "{}:{}: invalid hw address in {}: {}",
+                path.display(),
+                line,
+                key,
+                reason
+            ),
         }
+    }
+}
 
-        /* Look for DEVICE= */
-        if REGEX_DEVICE.is_match(&line) {
-            for capture in REGEX_DEVICE.captures_iter(&line) {
-                device = Some(capture[1].parse()?);
-            }
+impl error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ParseError::Io { source, .. } => Some(source),
+            ParseError::InvalidValue { source, .. } => Some(source),
+            ParseError::InvalidDeviceName { .. } => None,
+            ParseError::InvalidHwAddress { .. } => None,
+        }
+    }
+}
+
+/* Single `KEY=value` assignment of ifcfg file */
+#[derive(Debug)]
+pub struct Entry {
+    pub path: PathBuf,
+    pub line: usize,
+    pub key: String,
+    pub raw_value: String,
+    pub value: Result<String, UnescapeError>,
+}
+
+impl Entry {
+    /* Unescaped value of entry, or error pointing to the line where it is assigned */
+    pub fn value(&self) -> Result<&str, ParseError> {
+        match &self.value {
+            Ok(value) => Ok(value),
+            Err(err) => Err(ParseError::InvalidValue {
+                path: self.path.clone(),
+                line: self.line,
+                key: self.key.clone(),
+                source: err.clone(),
+            }),
         }
     }
 
-    if let Some(mac) = hwaddr {
-        if mac.to_string().to_lowercase().ne(mac_address) {
-            Err("new device name not found".into())
+    /* Value of entry read as hw address in any supported notation */
+    pub fn hwaddr(&self) -> Result<(HwAddress, Notation), ParseError> {
+        HwAddress::parse(self.value()?).map_err(|reason| ParseError::InvalidHwAddress {
+            path: self.path.clone(),
+            line: self.line,
+            key: self.key.clone(),
+            reason,
+        })
+    }
+}
+
+/* Kind of ifcfg file based on its name, only interface files describe physical network interfaces */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    /* ifcfg-eth0 */
+    Interface,
+    /* ifcfg-eth0:1 - additional address of interface */
+    Alias,
+    /* ifcfg-eth0-range0 - range of additional addresses of interface */
+    Range,
+}
+
+impl FileKind {
+    pub fn from_path(path: &Path) -> FileKind {
+        lazy_static! {
+            /* Look for name of alias file
+             * regex: ^ifcfg-[^:]+:.+$
+             * example: ifcfg-eth0:1 */
+            static ref REGEX_ALIAS: Regex = Regex::new(r"^ifcfg-[^:]+:.+$").unwrap();
+
+            /* Look for name of range file, same as initscripts do
+             * regex: ^ifcfg-.+-range
+             * example: ifcfg-eth0-range0 */
+            static ref REGEX_RANGE: Regex = Regex::new(r"^ifcfg-.+-range").unwrap();
+        }
+
+        let file_name = match path.file_name() {
+            Some(file_name) => file_name.to_string_lossy(),
+            None => return FileKind::Interface,
+        };
+
+        if REGEX_ALIAS.is_match(&file_name) {
+            FileKind::Alias
+        } else if REGEX_RANGE.is_match(&file_name) {
+            FileKind::Range
         } else {
-            Ok(device)
+            FileKind::Interface
+        }
+    }
+}
+
+impl fmt::Display for FileKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FileKind::Interface => write!(f, "interface"),
+            FileKind::Alias => write!(f, "alias"),
+            FileKind::Range => write!(f, "range"),
+        }
+    }
+}
+
+/* Content of ifcfg file as ordered list of assignments */
+#[derive(Debug)]
+pub struct IfcfgFile {
+    path: PathBuf,
+    entries: Vec<Entry>,
+    /* Assignments disabled by `#`, kept only to explain why the file doesn't match */
+    commented: Vec<Entry>,
+    /* Why the file can't be read, such file has no assignments */
+    read_error: Option<io::Error>,
+}
+
+impl IfcfgFile {
+    /* Read and parse ifcfg file */
+    pub fn open(path: &Path) -> Result<IfcfgFile, ParseError> {
+        let ifcfg = IfcfgFile::load(path);
+
+        match ifcfg.read_error() {
+            Some(err) => Err(err),
+            None => Ok(ifcfg),
+        }
+    }
+
+    /* Read and parse ifcfg file, file that can't be read is kept, so it can be reported with the others */
+    pub fn load(path: &Path) -> IfcfgFile {
+        match fs::read_to_string(path) {
+            Ok(content) => IfcfgFile::parse(path, &content),
+            Err(source) => IfcfgFile {
+                path: path.to_path_buf(),
+                entries: vec![],
+                commented: vec![],
+                read_error: Some(source),
+            },
+        }
+    }
+
+    /* Parse content of ifcfg file, path is used only to describe origin of entries */
+    pub fn parse(path: &Path, content: &str) -> IfcfgFile {
+        lazy_static! {
+            /* Look for line with shell variable assignment and store name of variable and its raw value in groups
+             * regex: ^\s*([A-Za-z_][A-Za-z0-9_]*)=(.*)$
+             * ^\s*(group1)=(group2)$ - look for line starting with variable name (ignore whitespaces) following with `=` and value
+             * group1: ([A-Za-z_][A-Za-z0-9_]*) - match valid shell variable name
+             * group2: (.*) - match raw value including quotes, escapes and trailing comment
+             * example: DEVICE="new-devname007" # comment
+             *          ^^^^^^ ^^^^^^^^^^^^^^^^^^^^^^^^^^
+             *          name   raw value */
+            static ref REGEX_ASSIGNMENT: Regex = Regex::new(r"^\s*([A-Za-z_][A-Za-z0-9_]*)=(.*)$").unwrap();
+
+            /* Look for assignment disabled by comment
+             * regex: ^\s*#+\s*([A-Za-z_][A-Za-z0-9_]*)=(.*)$
+             * example: #HWADDR=AA:BB:CC:DD:EE:FF
+             *           ^^^^^^ ^^^^^^^^^^^^^^^^^
+             *           name   raw value */
+            static ref REGEX_COMMENTED: Regex = Regex::new(r"^\s*#+\s*([A-Za-z_][A-Za-z0-9_]*)=(.*)$").unwrap();
+        }
+
+        let mut entries = vec![];
+        let mut commented = vec![];
+
+        for (index, line) in content.lines().enumerate() {
+            let (capture, list) = if let Some(capture) = REGEX_ASSIGNMENT.captures(line) {
+                (capture, &mut entries)
+            } else if let Some(capture) = REGEX_COMMENTED.captures(line) {
+                (capture, &mut commented)
+            } else {
+                continue;
+            };
+
+            let raw_value = capture[2].to_owned();
+
+            /* Values are read the same way as sourcing of ifcfg file by shell would */
+            list.push(Entry {
+                path: path.to_path_buf(),
+                line: index + 1,
+                key: capture[1].to_owned(),
+                value: shell::unescape(&raw_value),
+                raw_value,
+            });
+        }
+
+        IfcfgFile {
+            path: path.to_path_buf(),
+            entries,
+            commented,
+            read_error: None,
+        }
+    }
+
+    /* Error of reading the file, see IfcfgFile::load() */
+    pub fn read_error(&self) -> Option<ParseError> {
+        self.read_error.as_ref().map(|source| ParseError::Io {
+            path: self.path.clone(),
+            source: io::Error::new(source.kind(), source.to_string()),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn kind(&self) -> FileKind {
+        FileKind::from_path(&self.path)
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /* Last assignment of given key wins, same as in shell */
+    pub fn entry(&self, key: &str) -> Option<&Entry> {
+        self.entries.iter().rev().find(|entry| entry.key == key)
+    }
+
+    /* Last commented-out assignment of given key */
+    pub fn commented_entry(&self, key: &str) -> Option<&Entry> {
+        self.commented.iter().rev().find(|entry| entry.key == key)
+    }
+
+    /* Unescaped value of given key, empty value is treated as unset */
+    pub fn value(&self, key: &str) -> Result<Option<&str>, ParseError> {
+        match self.entry(key) {
+            Some(entry) => entry
+                .value()
+                .map(|value| Some(value).filter(|value| !value.is_empty())),
+            None => Ok(None),
+        }
+    }
+
+    /* DEVICE - name of network interface, never truncated */
+    pub fn device(&self) -> Result<Option<&str>, ParseError> {
+        let name = match self.value("DEVICE")? {
+            Some(name) => name,
+            None => return Ok(None),
+        };
+
+        match crate::validate_devname(name) {
+            Ok(()) => Ok(Some(name)),
+            Err(reason) => Err(ParseError::InvalidDeviceName {
+                path: self.path.clone(),
+                line: self.entry("DEVICE").map_or(0, |entry| entry.line),
+                name: name.to_owned(),
+                reason,
+            }),
+        }
+    }
+
+    /* HWADDR - permanent hw address of network interface */
+    pub fn hwaddr(&self) -> Result<Option<HwAddress>, ParseError> {
+        self.mac_address("HWADDR")
+    }
+
+    /* MACADDR - hw address to be assigned to network interface */
+    pub fn macaddr(&self) -> Result<Option<HwAddress>, ParseError> {
+        self.mac_address("MACADDR")
+    }
+
+    /* TYPE - type of network interface (Ethernet, Bond, InfiniBand, etc.) */
+    pub fn device_type(&self) -> Result<Option<&str>, ParseError> {
+        self.value("TYPE")
+    }
+
+    /* Return DEVICE name when HWADDR matches given hw address */
+    pub fn lookup(&self, mac_address: &HwAddress) -> Result<Option<&str>, ParseError> {
+        match self.hwaddr()? {
+            Some(hwaddr) if hwaddr.matches(mac_address) => self.device(),
+            _ => Ok(None),
+        }
+    }
+
+    fn mac_address(&self, key: &str) -> Result<Option<HwAddress>, ParseError> {
+        match self.value(key)? {
+            Some(_) => self
+                .entry(key)
+                .unwrap()
+                .hwaddr()
+                .map(|(hwaddr, _)| Some(hwaddr)),
+            None => Ok(None),
         }
-    } else {
-        Err("new device name not found".into())
     }
 }
 
@@ -81,13 +376,11 @@ pub mod should {
 
     #[test]
     fn parse_ifcfg_configuration() {
-        let mac_address = MacAddress::from_str("AA:BB:CC:DD:EE:3F")
-            .unwrap()
-            .to_string()
-            .to_lowercase();
+        let mac_address = HwAddress::from_str("AA:BB:CC:DD:EE:3F").unwrap();
         let ifcfg_config_path = Path::new(TEST_CONFIG_DIR).join("ifcfg-eth0");
 
-        let test_result = match config_file(&ifcfg_config_path, &mac_address) {
+        let ifcfg = IfcfgFile::open(&ifcfg_config_path).unwrap();
+        let test_result = match ifcfg.lookup(&mac_address) {
             Ok(Some(result)) => result.eq("correct_if_name"),
             _ => false,
         };
@@ -98,17 +391,97 @@ pub mod should {
     #[test]
     #[should_panic]
     fn not_parse_ifcfg_configuration() {
-        let mac_address = MacAddress::from_str("AA:BB:CC:DD:EE:4F")
-            .unwrap()
-            .to_string()
-            .to_lowercase();
+        let mac_address = HwAddress::from_str("AA:BB:CC:DD:EE:4F").unwrap();
         let ifcfg_config_path = Path::new(TEST_CONFIG_DIR).join("ifcfg-eth1");
 
-        let test_result = match config_file(&ifcfg_config_path, &mac_address) {
-            Ok(Some(_)) => true,
-            _ => false,
-        };
+        let ifcfg = IfcfgFile::open(&ifcfg_config_path).unwrap();
+        let test_result = matches!(ifcfg.lookup(&mac_address), Ok(Some(_)));
 
         assert!(test_result);
     }
+
+    #[test]
+    fn parse_ifcfg_entries() {
+        const CONTENT: &str =
+            "# comment\nDEVICE=lan0\n  TYPE=\"Ethernet\"\nNAME='broken\nDEVICE=\"lan1\"\n";
+
+        let ifcfg = IfcfgFile::parse(Path::new("ifcfg-lan"), CONTENT);
+        let keys: Vec<(usize, &str)> = ifcfg
+            .entries()
+            .iter()
+            .map(|entry| (entry.line, entry.key.as_str()))
+            .collect();
+
+        assert_eq!(
+            keys,
+            vec![(2, "DEVICE"), (3, "TYPE"), (4, "NAME"), (5, "DEVICE")]
+        );
+        assert_eq!(ifcfg.device().unwrap(), Some("lan1"));
+        assert_eq!(ifcfg.device_type().unwrap(), Some("Ethernet"));
+        assert!(matches!(
+            ifcfg.value("NAME"),
+            Err(ParseError::InvalidValue { line: 4, .. })
+        ));
+    }
+
+    #[test]
+    fn parse_commented_entries() {
+        const CONTENT: &str = "DEVICE=lan0
+#HWADDR=AA:BB:CC:DD:EE:01
+  # TYPE=Ethernet
+# just a comment
+";
+
+        let ifcfg = IfcfgFile::parse(Path::new("ifcfg-lan"), CONTENT);
+
+        assert_eq!(ifcfg.entries().len(), 1);
+        assert_eq!(ifcfg.hwaddr().unwrap(), None);
+        assert_eq!(ifcfg.commented_entry("HWADDR").unwrap().line, 2);
+        assert_eq!(
+            ifcfg.commented_entry("TYPE").unwrap().value.as_deref(),
+            Ok("Ethernet")
+        );
+        assert!(ifcfg.commented_entry("DEVICE").is_none());
+    }
+
+    #[test]
+    fn classify_ifcfg_files() {
+        for (name, kind) in [
+            ("ifcfg-eth0", FileKind::Interface),
+            ("ifcfg-br-lan", FileKind::Interface),
+            ("ifcfg-eth0:1", FileKind::Alias),
+            ("ifcfg-eth0:backup", FileKind::Alias),
+            ("ifcfg-eth0-range0", FileKind::Range),
+        ] {
+            let path = Path::new("/etc/sysconfig/network-scripts").join(name);
+            assert_eq!(FileKind::from_path(&path), kind, "{}", name);
+        }
+    }
+
+    #[test]
+    fn parse_ifcfg_hwaddr_notations() {
+        const CONTENT: &str = "HWADDR=001b.4411.3ab7\nMACADDR=00:1b:44:11:3a:zz\n";
+
+        let ifcfg = IfcfgFile::parse(Path::new("ifcfg-lan"), CONTENT);
+
+        assert_eq!(
+            ifcfg.hwaddr().unwrap(),
+            Some(HwAddress::from_str("00:1b:44:11:3a:b7").unwrap())
+        );
+        assert!(matches!(
+            ifcfg.entry("HWADDR").unwrap().hwaddr(),
+            Ok((_, Notation::Dot))
+        ));
+        assert!(matches!(
+            ifcfg.macaddr(),
+            Err(ParseError::InvalidHwAddress { line: 2, .. })
+        ));
+
+        let truncated = IfcfgFile::parse(Path::new("ifcfg-lan"), "HWADDR=00:1b:44:11:3a\n");
+
+        assert!(matches!(
+            truncated.hwaddr(),
+            Err(ParseError::InvalidHwAddress { line: 1, .. })
+        ));
+    }
 }
@@ -0,0 +1,182 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::collections::HashMap;
+use std::error;
+use std::path::Path;
+
+use log::*;
+
+use ifcfg_devname::exit::ExitCode;
+use ifcfg_devname::naming::{self, PlanStatus, PlanStep};
+use ifcfg_devname::options::Options;
+use ifcfg_devname::{IfcfgFile, Sysfs};
+
+use crate::cli::ListFormat;
+use crate::list;
+use crate::scanner::{self, ScanError};
+
+/* Resolve name of every interface on the host, fails when the plan can't be applied as a whole */
+pub fn run(
+    config_dir: &Path,
+    options: &Options,
+    sysfs: &Sysfs,
+    format: ListFormat,
+) -> Result<ExitCode, Box<dyn error::Error>> {
+    let ifcfgs = match scanner::load(config_dir, &options.ignored_suffixes) {
+        Ok(val) => val,
+        Err(ScanError::NoMatches(_)) => vec![],
+        Err(err) => {
+            error!("Fail to get list of ifcfg files: {}", err);
+            return Ok(ExitCode::from(&err));
+        }
+    };
+
+    let names = match sysfs.interfaces() {
+        Ok(val) => val,
+        Err(err) => {
+            error!(
+                "Fail to list network interfaces in '{}': {}",
+                sysfs.root().display(),
+                err
+            );
+            return Ok(ExitCode::InterfaceLookup);
+        }
+    };
+
+    let steps = plan(sysfs, options, &names, &ifcfgs);
+
+    print(&steps, format)?;
+
+    if steps
+        .iter()
+        .any(|step| matches!(step.status, PlanStatus::Collision | PlanStatus::Conflict))
+    {
+        return Ok(ExitCode::ConfigError);
+    }
+
+    Ok(ExitCode::Found)
+}
+
+fn plan(sysfs: &Sysfs, options: &Options, names: &[String], ifcfgs: &[IfcfgFile]) -> Vec<PlanStep> {
+    let mut steps: Vec<PlanStep> = names
+        .iter()
+        .map(|name| naming::step(sysfs, options, name, ifcfgs))
+        .collect();
+
+    /* Kernel refuses to rename interface to the name another one already got */
+    let mut targets: HashMap<String, usize> = HashMap::new();
+    for name in steps.iter().filter_map(|step| step.name.clone()) {
+        *targets.entry(name).or_default() += 1;
+    }
+
+    for step in &mut steps {
+        if matches!(&step.name, Some(name) if targets[name] > 1) {
+            warn!(
+                "'{}' would be named '{}' together with other interfaces",
+                step.interface,
+                step.name.as_deref().unwrap_or_default()
+            );
+            step.status = PlanStatus::Collision;
+        }
+    }
+
+    /* Name of other interface is free only when that interface is renamed too */
+    let staying: Vec<(String, Option<String>)> = steps
+        .iter()
+        .filter(|step| step.status != PlanStatus::Rename)
+        .map(|step| (step.interface.clone(), step.mac_address.clone()))
+        .collect();
+
+    for step in &mut steps {
+        if step.status != PlanStatus::Rename {
+            continue;
+        }
+
+        if let Some((owner, mac_address)) = staying
+            .iter()
+            .find(|(owner, _)| step.name.as_ref() == Some(owner))
+        {
+            warn!(
+                "'{}' would be named '{}', but the name belongs to interface with MAC address '{}'",
+                step.interface,
+                owner,
+                mac_address.as_deref().unwrap_or_default()
+            );
+            step.status = PlanStatus::Collision;
+        }
+    }
+
+    steps
+}
+
+fn print(steps: &[PlanStep], format: ListFormat) -> Result<(), serde_json::Error> {
+    const HEADER: [&str; 5] = ["INTERFACE", "HWADDR", "NAME", "FILE", "STATUS"];
+
+    let rows: Vec<[String; 5]> = steps
+        .iter()
+        .map(|step| {
+            [
+                step.interface.clone(),
+                step.mac_address.clone().unwrap_or_default(),
+                step.name.clone().unwrap_or_default(),
+                step.file
+                    .as_ref()
+                    .map(|file| file.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                step.status.to_string(),
+            ]
+        })
+        .collect();
+
+    match format {
+        ListFormat::Table => list::print_table(HEADER, &rows),
+        ListFormat::Json => println!("{}", serde_json::to_string_pretty(steps)?),
+        ListFormat::Csv => list::print_csv(HEADER, &rows),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod should {
+    use super::*;
+
+    const TEST_PLAN_DIR: &str = "./tests/unit_test_data/plan";
+
+    #[test]
+    fn plan_all_interfaces() {
+        let plan_dir = Path::new(TEST_PLAN_DIR);
+        let sysfs = Sysfs::new(&plan_dir.join("sysfs"));
+        let ifcfgs = scanner::load(&plan_dir.join("ifcfgs"), &[]).unwrap();
+
+        let steps = plan(
+            &sysfs,
+            &Options::default(),
+            &sysfs.interfaces().unwrap(),
+            &ifcfgs,
+        );
+        let statuses: Vec<(&str, PlanStatus)> = steps
+            .iter()
+            .map(|step| (step.interface.as_str(), step.status))
+            .collect();
+
+        assert_eq!(
+            statuses,
+            vec![
+                ("enp1s0", PlanStatus::Rename),
+                ("enp3s0", PlanStatus::Collision),
+                ("enp4s0", PlanStatus::Collision),
+                ("enp5s0", PlanStatus::Unmatched),
+                ("enp8s0", PlanStatus::Collision),
+                ("lan1", PlanStatus::Named),
+                ("lo", PlanStatus::NoAddress),
+                ("storage1", PlanStatus::Unmatched),
+                ("wlp6s0", PlanStatus::Untrusted),
+            ]
+        );
+        assert_eq!(steps[0].name.as_deref(), Some("lan0"));
+        assert_eq!(steps[0].mac_address.as_deref(), Some("52:54:00:00:00:01"));
+        assert_eq!(steps[0].file, Some(plan_dir.join("ifcfgs/ifcfg-lan0")));
+        assert_eq!(steps[2].name.as_deref(), Some("storage0"));
+    }
+}
@@ -0,0 +1,613 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::fmt;
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::exit::ExitCode;
+use crate::parser::{FileKind, ParseError};
+use crate::resolver::{Candidate, Outcome, Resolution};
+use crate::{HwAddress, IfcfgFile};
+
+/* Where hw address used for matching comes from */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MacOrigin {
+    /* Current hw address of the interface */
+    Current,
+    /* Permanent (burned-in) hw address of the interface */
+    Permanent,
+    /* hw address given using --mac */
+    CommandLine,
+}
+
+/* Issues that don't prevent naming, but deserve attention */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Warning {
+    /* New name looks like name assigned by kernel (eth0, etc.) */
+    KernelName,
+    /* More ifcfg files match, but they disagree on DEVICE */
+    Conflict,
+    /* More ifcfg files with the same HWADDR agree on DEVICE */
+    DuplicateHwaddr,
+    /* More ifcfg files set the same DEVICE for different HWADDR */
+    DuplicateDevice,
+    /* HWADDR can't be parsed */
+    InvalidHwaddr,
+    /* DEVICE is set, but HWADDR isn't */
+    NoHwaddr,
+    /* DEVICE is refused by kernel */
+    InvalidDevice,
+    /* HWADDR is set, but DEVICE isn't */
+    NoDevice,
+    /* Alias or range file, it's never used for naming */
+    Excluded,
+    /* New name already belongs to another interface */
+    NameTaken,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Warning::KernelName => write!(f, "kernel-name"),
+            Warning::Conflict => write!(f, "conflict"),
+            Warning::DuplicateHwaddr => write!(f, "duplicate-hwaddr"),
+            Warning::DuplicateDevice => write!(f, "duplicate-device"),
+            Warning::InvalidHwaddr => write!(f, "invalid-hwaddr"),
+            Warning::NoHwaddr => write!(f, "no-hwaddr"),
+            Warning::InvalidDevice => write!(f, "invalid-device"),
+            Warning::NoDevice => write!(f, "no-device"),
+            Warning::Excluded => write!(f, "excluded"),
+            Warning::NameTaken => write!(f, "name-taken"),
+        }
+    }
+}
+
+/* How single ifcfg file was matched against hw address of the interface */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileReport {
+    pub path: PathBuf,
+    pub kind: String,
+    /* matched, no-hwaddr, only-macaddr, mismatch, no-device, error or excluded */
+    pub outcome: &'static str,
+    pub hwaddr: Option<String>,
+    pub device: Option<String>,
+    pub error: Option<String>,
+    /* Why the file was or wasn't used, in words */
+    pub reason: String,
+}
+
+impl FileReport {
+    pub fn new(candidate: &Candidate) -> FileReport {
+        let ifcfg = candidate.ifcfg;
+        let hwaddr = ifcfg
+            .hwaddr()
+            .ok()
+            .flatten()
+            .map(|hwaddr| hwaddr.to_string());
+
+        let (outcome, error) = match &candidate.outcome {
+            Outcome::Matched(_) => ("matched", None),
+            Outcome::NoHwaddr => ("no-hwaddr", None),
+            Outcome::OnlyMacaddr => ("only-macaddr", None),
+            Outcome::Mismatch => ("mismatch", None),
+            Outcome::NoDevice => ("no-device", None),
+            Outcome::Error(err) => ("error", Some(err.to_string())),
+            Outcome::Excluded { .. } => ("excluded", None),
+        };
+
+        let reason = match &candidate.outcome {
+            Outcome::Matched(name) => format!("HWADDR matches, DEVICE is '{}'", name),
+            Outcome::NoHwaddr => not_set(ifcfg, "HWADDR"),
+            Outcome::OnlyMacaddr => {
+                String::from("only MACADDR is set, it is hw address to assign, not to match")
+            }
+            Outcome::Mismatch => format!(
+                "HWADDR '{}' doesn't match",
+                hwaddr.as_deref().unwrap_or_default()
+            ),
+            Outcome::NoDevice => format!("HWADDR matches, but {}", not_set(ifcfg, "DEVICE")),
+            Outcome::Error(err) => err.to_string(),
+            Outcome::Excluded {
+                kind,
+                hijacked: Some(name),
+            } => format!(
+                "{} files are never used for naming, its DEVICE '{}' is ignored",
+                kind, name
+            ),
+            Outcome::Excluded { kind, .. } => {
+                format!("{} files are never used for naming", kind)
+            }
+        };
+
+        FileReport {
+            path: ifcfg.path().to_path_buf(),
+            kind: ifcfg.kind().to_string(),
+            outcome,
+            hwaddr,
+            device: ifcfg.device().ok().flatten().map(String::from),
+            error,
+            reason,
+        }
+    }
+}
+
+/* Other interface that already has the new name */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Collision {
+    pub interface: String,
+    pub mac_address: Option<String>,
+    /* Name the other interface is going to be renamed to, the new name is free after that */
+    pub eventually: Option<String>,
+}
+
+impl fmt::Display for Collision {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "name already belongs to '{}'", self.interface)?;
+        if let Some(mac_address) = &self.mac_address {
+            write!(f, " with MAC address '{}'", mac_address)?;
+        }
+
+        match &self.eventually {
+            Some(name) => write!(
+                f,
+                ", it is going to be renamed to '{}', retry after that",
+                name
+            ),
+            None => write!(f, ", it isn't going to be renamed"),
+        }
+    }
+}
+
+/* ifcfg file skipped during scanning because of its suffix */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IgnoredFile {
+    pub path: PathBuf,
+    pub suffix: String,
+}
+
+/* Whole resolution of new name of the interface, printed by --format json and --explain */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Report {
+    pub interface: String,
+    /* None when hw address of the interface can't be obtained */
+    pub mac_address: Option<String>,
+    pub mac_origin: Option<MacOrigin>,
+    pub files: Vec<FileReport>,
+    pub ignored: Vec<IgnoredFile>,
+    pub name: Option<String>,
+    pub collision: Option<Collision>,
+    pub file: Option<PathBuf>,
+    pub warnings: Vec<Warning>,
+    /* Final decision, in words */
+    pub decision: String,
+    pub exit_code: i32,
+}
+
+impl Report {
+    pub fn new(
+        interface: &str,
+        mac_address: &HwAddress,
+        mac_origin: MacOrigin,
+        resolution: &Resolution,
+        selected: Option<(&IfcfgFile, &str)>,
+        warnings: &[Warning],
+        exit_code: ExitCode,
+    ) -> Report {
+        let decision = match (selected, exit_code) {
+            (Some((ifcfg, name)), ExitCode::NameTaken) => format!(
+                "name '{}' from '{}' already belongs to another interface, the interface keeps its name",
+                name,
+                ifcfg.path().display()
+            ),
+            (Some((ifcfg, name)), _) => format!(
+                "interface is named '{}' according to '{}'",
+                name,
+                ifcfg.path().display()
+            ),
+            (None, ExitCode::ConfigError) => match resolution.conflict() {
+                Some(conflict) => format!("{}, refusing to rename the interface", conflict),
+                None => String::from("ifcfg files can't be read, the interface keeps its name"),
+            },
+            (None, ExitCode::InvalidName) => String::from(
+                "matching ifcfg file sets DEVICE kernel refuses, the interface keeps its name",
+            ),
+            (None, _) => format!(
+                "no ifcfg file sets HWADDR '{}' together with DEVICE{}, the interface keeps its name",
+                mac_address,
+                unreadable(resolution)
+            ),
+        };
+
+        Report {
+            interface: interface.to_owned(),
+            mac_address: Some(mac_address.to_string()),
+            mac_origin: Some(mac_origin),
+            files: resolution.candidates.iter().map(FileReport::new).collect(),
+            ignored: vec![],
+            name: selected.map(|(_, name)| name.to_owned()),
+            collision: None,
+            file: selected.map(|(ifcfg, _)| ifcfg.path().to_path_buf()),
+            warnings: warnings.to_vec(),
+            decision,
+            exit_code: exit_code.code(),
+        }
+    }
+
+    /* Resolution stopped before ifcfg files were matched, decision tells the failing step */
+    pub fn failed(
+        interface: &str,
+        mac_address: Option<(&HwAddress, MacOrigin)>,
+        decision: String,
+        exit_code: ExitCode,
+    ) -> Report {
+        Report {
+            interface: interface.to_owned(),
+            mac_address: mac_address.map(|(mac_address, _)| mac_address.to_string()),
+            mac_origin: mac_address.map(|(_, mac_origin)| mac_origin),
+            files: vec![],
+            ignored: vec![],
+            name: None,
+            collision: None,
+            file: None,
+            warnings: vec![],
+            decision,
+            exit_code: exit_code.code(),
+        }
+    }
+}
+
+/* hw address to name mapping described by single ifcfg file */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Mapping {
+    /* Normalized HWADDR, None when it isn't set or can't be parsed */
+    pub hwaddr: Option<String>,
+    pub device: Option<String>,
+    pub file: PathBuf,
+    pub warnings: Vec<Warning>,
+    /* Why HWADDR can't be used, in words */
+    pub reason: Option<String>,
+}
+
+impl Mapping {
+    /* Mappings of all ifcfg files that set HWADDR, in the same order as the files */
+    pub fn collect(ifcfgs: &[IfcfgFile]) -> Vec<Mapping> {
+        ifcfgs
+            .iter()
+            .filter(|ifcfg| ifcfg.entry("HWADDR").is_some())
+            .map(|ifcfg| Mapping::new(ifcfg, ifcfgs))
+            .collect()
+    }
+
+    /* Mappings of interface files setting given DEVICE, there should be just one
+     * Files without usable HWADDR are kept, they tell why the name can't be assigned */
+    pub fn by_name(ifcfgs: &[IfcfgFile], name: &str) -> Vec<Mapping> {
+        ifcfgs
+            .iter()
+            .filter(|ifcfg| {
+                ifcfg.kind() == FileKind::Interface
+                    && written_value(ifcfg, "DEVICE").as_deref() == Some(name)
+            })
+            .map(|ifcfg| Mapping::new(ifcfg, ifcfgs))
+            .collect()
+    }
+
+    fn new(ifcfg: &IfcfgFile, ifcfgs: &[IfcfgFile]) -> Mapping {
+        let mut warnings = vec![];
+
+        if ifcfg.kind() != FileKind::Interface {
+            warnings.push(Warning::Excluded);
+        }
+
+        let hwaddr = match ifcfg.hwaddr() {
+            Ok(Some(hwaddr)) => Ok(hwaddr),
+            Ok(None) => {
+                warnings.push(Warning::NoHwaddr);
+                Err(match ifcfg.macaddr() {
+                    Ok(Some(macaddr)) => format!(
+                        "only MACADDR '{}' is set, it is hw address to assign, not to match",
+                        macaddr
+                    ),
+                    _ => not_set(ifcfg, "HWADDR"),
+                })
+            }
+            Err(ParseError::InvalidHwAddress { reason, .. }) => {
+                warnings.push(Warning::InvalidHwaddr);
+                Err(format!("invalid HWADDR: {}", reason))
+            }
+            Err(err) => {
+                warnings.push(Warning::InvalidHwaddr);
+                Err(err.to_string())
+            }
+        };
+
+        let device = match ifcfg.device() {
+            Ok(Some(device)) => {
+                if crate::is_like_kernel_name(device) {
+                    warnings.push(Warning::KernelName);
+                }
+                Some(device.to_owned())
+            }
+            Ok(None) => {
+                warnings.push(Warning::NoDevice);
+                None
+            }
+            Err(_) => {
+                warnings.push(Warning::InvalidDevice);
+                written_value(ifcfg, "DEVICE")
+            }
+        };
+
+        /* Only interface files compete for the same hw address */
+        if let (Ok(hwaddr), FileKind::Interface) = (&hwaddr, ifcfg.kind()) {
+            let others: Vec<Option<&str>> = ifcfgs
+                .iter()
+                .filter(|other| other.path() != ifcfg.path() && other.kind() == FileKind::Interface)
+                .filter(|other| matches!(other.hwaddr(), Ok(Some(other)) if other.matches(hwaddr)))
+                .map(|other| other.device().ok().flatten())
+                .collect();
+
+            if others.iter().any(|other| *other != device.as_deref()) {
+                warnings.push(Warning::Conflict);
+            } else if !others.is_empty() {
+                warnings.push(Warning::DuplicateHwaddr);
+            }
+
+            /* Two interfaces can't have the same name, only one of them would be renamed */
+            if device.is_some()
+                && ifcfgs.iter().any(|other| {
+                    other.kind() == FileKind::Interface
+                        && other.device().ok().flatten() == device.as_deref()
+                        && matches!(other.hwaddr(), Ok(Some(other)) if !other.matches(hwaddr))
+                })
+            {
+                warnings.push(Warning::DuplicateDevice);
+            }
+        }
+
+        Mapping {
+            hwaddr: hwaddr.as_ref().ok().map(|hwaddr| hwaddr.to_string()),
+            device,
+            file: ifcfg.path().to_path_buf(),
+            warnings,
+            reason: hwaddr.err(),
+        }
+    }
+}
+
+/* Files that can't be read may be the missing match, tell about them in the decision */
+fn unreadable(resolution: &Resolution) -> String {
+    let paths: Vec<String> = resolution
+        .candidates
+        .iter()
+        .filter(|candidate| matches!(candidate.outcome, Outcome::Error(ParseError::Io { .. })))
+        .map(|candidate| format!("'{}'", candidate.ifcfg.path().display()))
+        .collect();
+
+    if paths.is_empty() {
+        return String::new();
+    }
+
+    format!(", but {} can't be read", paths.join(", "))
+}
+
+/* Mention commented-out assignment, it's a common reason why file doesn't match */
+fn not_set(ifcfg: &IfcfgFile, key: &str) -> String {
+    match ifcfg.commented_entry(key) {
+        Some(entry) => format!("{} is commented out on line {}", key, entry.line),
+        None => format!("{} isn't set", key),
+    }
+}
+
+/* Value as written in ifcfg file, unescaped when possible */
+fn written_value(ifcfg: &IfcfgFile, key: &str) -> Option<String> {
+    ifcfg.entry(key).map(|entry| {
+        entry
+            .value
+            .clone()
+            .unwrap_or_else(|_| entry.raw_value.clone())
+    })
+}
+
+#[cfg(test)]
+pub mod should {
+    use super::*;
+
+    use std::path::Path;
+
+    use crate::resolver::{self, Precedence};
+
+    #[test]
+    fn report_resolution() {
+        let ifcfgs = vec![
+            IfcfgFile::parse(
+                Path::new("ifcfg-a"),
+                "DEVICE=lan0\nHWADDR=AA:BB:CC:DD:EE:01\n",
+            ),
+            IfcfgFile::parse(
+                Path::new("ifcfg-b"),
+                "DEVICE=lan1\nHWADDR=AA:BB:CC:DD:EE:02\n",
+            ),
+            IfcfgFile::parse(
+                Path::new("ifcfg-c"),
+                "DEVICE=lan2\n#HWADDR=AA:BB:CC:DD:EE:03\n",
+            ),
+        ];
+        let mac_address: HwAddress = "AA:BB:CC:DD:EE:01".parse().unwrap();
+        let resolution = resolver::resolve(&ifcfgs, &mac_address);
+
+        let report = Report::new(
+            "eth0",
+            &mac_address,
+            MacOrigin::Current,
+            &resolution,
+            resolution.select(Precedence::First).unwrap(),
+            &[],
+            ExitCode::Found,
+        );
+        let json = serde_json::to_value(&report).unwrap();
+
+        assert_eq!(json["mac_address"], "aa:bb:cc:dd:ee:01");
+        assert_eq!(json["mac_origin"], "current");
+        assert_eq!(json["name"], "lan0");
+        assert_eq!(json["file"], "ifcfg-a");
+        assert_eq!(json["exit_code"], 0);
+        assert_eq!(json["files"][0]["outcome"], "matched");
+        assert_eq!(json["files"][0]["kind"], "interface");
+        assert_eq!(json["files"][1]["outcome"], "mismatch");
+        assert_eq!(json["files"][1]["hwaddr"], "aa:bb:cc:dd:ee:02");
+        assert_eq!(json["files"][1]["device"], "lan1");
+        assert_eq!(json["files"][2]["outcome"], "no-hwaddr");
+        assert_eq!(
+            json["files"][2]["reason"],
+            "HWADDR is commented out on line 2"
+        );
+        assert_eq!(
+            json["decision"],
+            "interface is named 'lan0' according to 'ifcfg-a'"
+        );
+    }
+
+    #[test]
+    fn report_unreadable_files() {
+        let ifcfgs = vec![
+            IfcfgFile::parse(
+                Path::new("ifcfg-a"),
+                "DEVICE=lan0\nHWADDR=AA:BB:CC:DD:EE:01\n",
+            ),
+            IfcfgFile::load(Path::new("./tests/unit_test_data/unreadable/ifcfg-lan1")),
+        ];
+        let mac_address: HwAddress = "AA:BB:CC:DD:EE:02".parse().unwrap();
+        let resolution = resolver::resolve(&ifcfgs, &mac_address);
+
+        let report = Report::new(
+            "eth0",
+            &mac_address,
+            MacOrigin::Current,
+            &resolution,
+            None,
+            &[],
+            ExitCode::NoMatch,
+        );
+        let json = serde_json::to_value(&report).unwrap();
+
+        assert_eq!(json["files"][0]["outcome"], "error");
+        assert!(json["files"][0]["error"]
+            .as_str()
+            .unwrap()
+            .contains("fail to read file"));
+        assert_eq!(
+            json["decision"],
+            "no ifcfg file sets HWADDR 'aa:bb:cc:dd:ee:02' together with DEVICE, but './tests/unit_test_data/unreadable/ifcfg-lan1' can't be read, the interface keeps its name"
+        );
+    }
+
+    #[test]
+    fn collect_mappings() {
+        let ifcfgs = vec![
+            IfcfgFile::parse(
+                Path::new("ifcfg-a"),
+                "DEVICE=lan0\nHWADDR=AA:BB:CC:DD:EE:01\n",
+            ),
+            IfcfgFile::parse(
+                Path::new("ifcfg-b"),
+                "DEVICE=lan1\nHWADDR=aa-bb-cc-dd-ee-01\n",
+            ),
+            IfcfgFile::parse(
+                Path::new("ifcfg-c"),
+                "DEVICE=eth2\nHWADDR=AA:BB:CC:DD:EE:02\n",
+            ),
+            IfcfgFile::parse(Path::new("ifcfg-d"), "HWADDR=AA:BB:CC:DD:EE:XY\n"),
+            IfcfgFile::parse(Path::new("ifcfg-e"), "DEVICE=lan4\n"),
+            IfcfgFile::parse(
+                Path::new("ifcfg-a:1"),
+                "DEVICE=lan0\nHWADDR=AA:BB:CC:DD:EE:01\n",
+            ),
+        ];
+
+        let mappings = Mapping::collect(&ifcfgs);
+
+        assert_eq!(mappings.len(), 5);
+        assert_eq!(mappings[0].hwaddr.as_deref(), Some("aa:bb:cc:dd:ee:01"));
+        assert_eq!(mappings[0].warnings, vec![Warning::Conflict]);
+        assert_eq!(mappings[1].device.as_deref(), Some("lan1"));
+        assert_eq!(mappings[2].warnings, vec![Warning::KernelName]);
+        assert_eq!(mappings[3].hwaddr, None);
+        assert_eq!(
+            mappings[3].warnings,
+            vec![Warning::InvalidHwaddr, Warning::NoDevice]
+        );
+        assert_eq!(
+            mappings[3].reason.as_deref(),
+            Some("invalid HWADDR: 'AA:BB:CC:DD:EE:XY' isn't valid hw address")
+        );
+        assert_eq!(mappings[4].warnings, vec![Warning::Excluded]);
+    }
+
+    #[test]
+    fn find_mappings_by_name() {
+        let ifcfgs = vec![
+            IfcfgFile::parse(
+                Path::new("ifcfg-a"),
+                "DEVICE=storage0\nHWADDR=AA:BB:CC:DD:EE:01\n",
+            ),
+            IfcfgFile::parse(
+                Path::new("ifcfg-b"),
+                "DEVICE=lan0\nHWADDR=AA:BB:CC:DD:EE:02\n",
+            ),
+            IfcfgFile::parse(
+                Path::new("ifcfg-c"),
+                "DEVICE=storage0\nHWADDR=AA:BB:CC:DD:EE:03\n",
+            ),
+            IfcfgFile::parse(
+                Path::new("ifcfg-c:1"),
+                "DEVICE=storage0\nHWADDR=AA:BB:CC:DD:EE:03\n",
+            ),
+            IfcfgFile::parse(
+                Path::new("ifcfg-d"),
+                "DEVICE=lan1\n#HWADDR=AA:BB:CC:DD:EE:04\n",
+            ),
+            IfcfgFile::parse(
+                Path::new("ifcfg-e"),
+                "DEVICE=lan1\nMACADDR=AA:BB:CC:DD:EE:05\n",
+            ),
+            IfcfgFile::parse(Path::new("ifcfg-f"), "DEVICE=lan1\nHWADDR=AA:BB:CC:DD:EE\n"),
+        ];
+
+        let mappings = Mapping::by_name(&ifcfgs, "storage0");
+
+        assert_eq!(mappings.len(), 2);
+        assert_eq!(mappings[0].hwaddr.as_deref(), Some("aa:bb:cc:dd:ee:01"));
+        assert_eq!(mappings[0].warnings, vec![Warning::DuplicateDevice]);
+        assert_eq!(mappings[1].file, PathBuf::from("ifcfg-c"));
+        assert!(Mapping::by_name(&ifcfgs, "lan0")[0].warnings.is_empty());
+        assert!(Mapping::by_name(&ifcfgs, "lan9").is_empty());
+
+        /* Files without usable HWADDR are found too, with the reason */
+        let unusable: Vec<(Option<String>, Option<String>)> = Mapping::by_name(&ifcfgs, "lan1")
+            .into_iter()
+            .map(|mapping| (mapping.hwaddr, mapping.reason))
+            .collect();
+
+        assert_eq!(
+            unusable,
+            vec![
+                (None, Some(String::from("HWADDR is commented out on line 2"))),
+                (
+                    None,
+                    Some(String::from(
+                        "only MACADDR 'aa:bb:cc:dd:ee:05' is set, it is hw address to assign, not to match"
+                    ))
+                ),
+                (
+                    None,
+                    Some(String::from(
+                        "invalid HWADDR: 'AA:BB:CC:DD:EE' has 5 bytes, expected 6 (Ethernet), 8 (EUI-64) or 20 (InfiniBand)"
+                    ))
+                ),
+            ]
+        );
+    }
+}
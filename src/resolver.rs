@@ -0,0 +1,335 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::error;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::parser::{FileKind, ParseError};
+use crate::{HwAddress, IfcfgFile};
+
+/* Which ifcfg file wins when more of them match given hw address but disagree on DEVICE */
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Precedence {
+    /* First matching file in alphabetical order of paths wins */
+    #[default]
+    First,
+    /* Last matching file in alphabetical order of paths wins */
+    Last,
+    /* Don't rename the interface at all */
+    Refuse,
+}
+
+impl FromStr for Precedence {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "first" => Ok(Precedence::First),
+            "last" => Ok(Precedence::Last),
+            "refuse" => Ok(Precedence::Refuse),
+            _ => Err(format!(
+                "unknown precedence '{}', expected 'first', 'last' or 'refuse'",
+                value
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Precedence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Precedence::First => write!(f, "first"),
+            Precedence::Last => write!(f, "last"),
+            Precedence::Refuse => write!(f, "refuse"),
+        }
+    }
+}
+
+/* Result of matching single ifcfg file against hw address of network interface */
+#[derive(Debug)]
+pub enum Outcome<'a> {
+    Matched(&'a str),
+    NoHwaddr,
+    OnlyMacaddr,
+    Mismatch,
+    NoDevice,
+    Error(ParseError),
+    /* Alias and range files never name interfaces, hijacked is DEVICE they would match otherwise */
+    Excluded {
+        kind: FileKind,
+        hijacked: Option<&'a str>,
+    },
+}
+
+#[derive(Debug)]
+pub struct Candidate<'a> {
+    pub ifcfg: &'a IfcfgFile,
+    pub outcome: Outcome<'a>,
+}
+
+/* More ifcfg files match the same hw address, but they disagree on DEVICE */
+#[derive(Debug)]
+pub struct Conflict {
+    pub mac_address: HwAddress,
+    pub matches: Vec<(PathBuf, String)>,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let matches: Vec<String> = self
+            .matches
+            .iter()
+            .map(|(path, name)| format!("'{}' ({})", name, path.display()))
+            .collect();
+
+        write!(
+            f,
+            "ifcfg files disagree on name of device with MAC address '{}': {}",
+            self.mac_address,
+            matches.join(", ")
+        )
+    }
+}
+
+impl error::Error for Conflict {}
+
+/* Outcomes of all ifcfg files, sorted by their paths so the result doesn't depend on order of scanning */
+#[derive(Debug)]
+pub struct Resolution<'a> {
+    pub mac_address: HwAddress,
+    pub candidates: Vec<Candidate<'a>>,
+}
+
+impl<'a> Resolution<'a> {
+    pub fn matches(&self) -> impl Iterator<Item = (&'a IfcfgFile, &'a str)> + '_ {
+        self.candidates
+            .iter()
+            .filter_map(|candidate| match candidate.outcome {
+                Outcome::Matched(name) => Some((candidate.ifcfg, name)),
+                _ => None,
+            })
+    }
+
+    /* Report all matching files when they don't agree on DEVICE */
+    pub fn conflict(&self) -> Option<Conflict> {
+        let mut matches = self.matches();
+        let (_, first_name) = matches.next()?;
+
+        if matches.all(|(_, name)| name == first_name) {
+            return None;
+        }
+
+        Some(Conflict {
+            mac_address: self.mac_address.clone(),
+            matches: self
+                .matches()
+                .map(|(ifcfg, name)| (ifcfg.path().to_path_buf(), name.to_owned()))
+                .collect(),
+        })
+    }
+
+    /* Choose new name of network interface according to precedence */
+    pub fn select(
+        &self,
+        precedence: Precedence,
+    ) -> Result<Option<(&'a IfcfgFile, &'a str)>, Conflict> {
+        match precedence {
+            Precedence::First => Ok(self.matches().next()),
+            Precedence::Last => Ok(self.matches().last()),
+            Precedence::Refuse => match self.conflict() {
+                Some(conflict) => Err(conflict),
+                None => Ok(self.matches().next()),
+            },
+        }
+    }
+}
+
+/* Match every ifcfg file against hw address of network interface */
+pub fn resolve<'a>(ifcfgs: &'a [IfcfgFile], mac_address: &HwAddress) -> Resolution<'a> {
+    let mut sorted: Vec<&IfcfgFile> = ifcfgs.iter().collect();
+    sorted.sort_by(|a, b| a.path().cmp(b.path()));
+
+    let candidates = sorted
+        .into_iter()
+        .map(|ifcfg| Candidate {
+            ifcfg,
+            outcome: outcome(ifcfg, mac_address),
+        })
+        .collect();
+
+    Resolution {
+        mac_address: mac_address.clone(),
+        candidates,
+    }
+}
+
+fn outcome<'a>(ifcfg: &'a IfcfgFile, mac_address: &HwAddress) -> Outcome<'a> {
+    if let Some(err) = ifcfg.read_error() {
+        return Outcome::Error(err);
+    }
+
+    match ifcfg.kind() {
+        FileKind::Interface => match_hwaddr(ifcfg, mac_address),
+        kind => Outcome::Excluded {
+            kind,
+            hijacked: match match_hwaddr(ifcfg, mac_address) {
+                Outcome::Matched(name) => Some(name),
+                _ => None,
+            },
+        },
+    }
+}
+
+fn match_hwaddr<'a>(ifcfg: &'a IfcfgFile, mac_address: &HwAddress) -> Outcome<'a> {
+    /* MACADDR is hw address to assign, matching is done only using HWADDR */
+    match ifcfg.hwaddr() {
+        Ok(Some(hwaddr)) if hwaddr.matches(mac_address) => {}
+        Ok(Some(_)) => return Outcome::Mismatch,
+        Ok(None) if matches!(ifcfg.macaddr(), Ok(Some(_))) => return Outcome::OnlyMacaddr,
+        Ok(None) => return Outcome::NoHwaddr,
+        Err(err) => return Outcome::Error(err),
+    }
+
+    match ifcfg.device() {
+        Ok(Some(name)) => Outcome::Matched(name),
+        Ok(None) => Outcome::NoDevice,
+        Err(err) => Outcome::Error(err),
+    }
+}
+
+/* Alias or range file setting HWADDR of interface file, but DEVICE of other interface */
+#[derive(Debug)]
+pub struct Hijack<'a> {
+    pub owner: &'a IfcfgFile,
+    pub hwaddr: HwAddress,
+    pub device: &'a str,
+    pub owner_device: &'a str,
+}
+
+/* Alias (lan0:1) and range files belong to interface whose DEVICE is before the colon */
+pub fn hijack<'a>(ifcfg: &'a IfcfgFile, ifcfgs: &'a [IfcfgFile]) -> Option<Hijack<'a>> {
+    if ifcfg.kind() == FileKind::Interface {
+        return None;
+    }
+
+    let hwaddr = ifcfg.hwaddr().ok().flatten()?;
+    let device = ifcfg.value("DEVICE").ok().flatten()?;
+
+    let owner = ifcfgs.iter().find(|other| {
+        other.kind() == FileKind::Interface
+            && matches!(other.hwaddr(), Ok(Some(other)) if other.matches(&hwaddr))
+    })?;
+    let owner_device = owner.value("DEVICE").ok().flatten()?;
+
+    if device.split(':').next() == Some(owner_device) {
+        return None;
+    }
+
+    Some(Hijack {
+        owner,
+        hwaddr,
+        device,
+        owner_device,
+    })
+}
+
+#[cfg(test)]
+pub mod should {
+    use super::*;
+
+    use std::path::Path;
+
+    fn ifcfgs() -> Vec<IfcfgFile> {
+        vec![
+            IfcfgFile::parse(
+                Path::new("ifcfg-b"),
+                "DEVICE=lan1\nHWADDR=AA:BB:CC:DD:EE:01\n",
+            ),
+            IfcfgFile::parse(
+                Path::new("ifcfg-a"),
+                "DEVICE=lan0\nHWADDR=AA:BB:CC:DD:EE:01\n",
+            ),
+            IfcfgFile::parse(
+                Path::new("ifcfg-c"),
+                "DEVICE=lan2\nMACADDR=AA:BB:CC:DD:EE:01\n",
+            ),
+            IfcfgFile::parse(
+                Path::new("ifcfg-a:1"),
+                "DEVICE=lan3\nHWADDR=AA:BB:CC:DD:EE:01\n",
+            ),
+        ]
+    }
+
+    #[test]
+    fn resolve_conflict_by_precedence() {
+        let ifcfgs = ifcfgs();
+        let mac_address: HwAddress = "AA:BB:CC:DD:EE:01".parse().unwrap();
+
+        let resolution = resolve(&ifcfgs, &mac_address);
+
+        assert!(matches!(
+            resolution.candidates[1].outcome,
+            Outcome::Excluded {
+                kind: FileKind::Alias,
+                hijacked: Some("lan3")
+            }
+        ));
+        assert!(matches!(
+            resolution.candidates[3].outcome,
+            Outcome::OnlyMacaddr
+        ));
+        assert_eq!(resolution.conflict().unwrap().matches.len(), 2);
+        assert_eq!(
+            resolution.select(Precedence::First).unwrap().unwrap().1,
+            "lan0"
+        );
+        assert_eq!(
+            resolution.select(Precedence::Last).unwrap().unwrap().1,
+            "lan1"
+        );
+        assert!(resolution.select(Precedence::Refuse).is_err());
+    }
+
+    #[test]
+    fn not_resolve_unknown_mac_address() {
+        let ifcfgs = ifcfgs();
+        let mac_address: HwAddress = "AA:BB:CC:DD:EE:02".parse().unwrap();
+
+        let resolution = resolve(&ifcfgs, &mac_address);
+
+        assert!(resolution.conflict().is_none());
+        assert!(resolution.select(Precedence::Refuse).unwrap().is_none());
+    }
+
+    #[test]
+    fn detect_alias_hijacking_other_interface() {
+        let ifcfgs = vec![
+            IfcfgFile::parse(
+                Path::new("ifcfg-lan0"),
+                "DEVICE=lan0\nHWADDR=AA:BB:CC:DD:EE:01\n",
+            ),
+            IfcfgFile::parse(
+                Path::new("ifcfg-lan0:1"),
+                "DEVICE=lan0:1\nHWADDR=AA:BB:CC:DD:EE:01\n",
+            ),
+            IfcfgFile::parse(
+                Path::new("ifcfg-lan0:2"),
+                "DEVICE=lan3\nHWADDR=AA:BB:CC:DD:EE:01\n",
+            ),
+            IfcfgFile::parse(
+                Path::new("ifcfg-lan9:1"),
+                "DEVICE=lan9\nHWADDR=AA:BB:CC:DD:EE:09\n",
+            ),
+        ];
+
+        assert!(hijack(&ifcfgs[0], &ifcfgs).is_none());
+        assert!(hijack(&ifcfgs[1], &ifcfgs).is_none());
+        assert!(hijack(&ifcfgs[3], &ifcfgs).is_none());
+
+        let hijack = hijack(&ifcfgs[2], &ifcfgs).unwrap();
+        assert_eq!(hijack.owner.path(), Path::new("ifcfg-lan0"));
+        assert_eq!(hijack.device, "lan3");
+        assert_eq!(hijack.owner_device, "lan0");
+    }
+}
@@ -0,0 +1,73 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::error;
+use std::path::Path;
+
+use log::*;
+
+use ifcfg_devname::exit::ExitCode;
+use ifcfg_devname::options::Options;
+use ifcfg_devname::report::Mapping;
+
+use crate::cli::ListFormat;
+use crate::list;
+use crate::scanner;
+
+/* Look up hw address and ifcfg file by DEVICE, e.g. when network card is being replaced */
+pub fn run(
+    config_dir: &Path,
+    options: &Options,
+    name: &str,
+    format: ListFormat,
+) -> Result<ExitCode, Box<dyn error::Error>> {
+    let ifcfgs = match scanner::load(config_dir, &options.ignored_suffixes) {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Fail to get list of ifcfg files: {}", err);
+            return Ok(ExitCode::from(&err));
+        }
+    };
+
+    let mappings = Mapping::by_name(&ifcfgs, name);
+
+    if mappings.len() > 1 {
+        let files: Vec<String> = mappings
+            .iter()
+            .map(|mapping| format!("'{}'", mapping.file.display()))
+            .collect();
+        warn!(
+            "DEVICE '{}' is set by more ifcfg files: {}",
+            name,
+            files.join(", ")
+        );
+    }
+
+    list::print(&mappings, format)?;
+
+    if mappings.is_empty() {
+        error!("No ifcfg file sets DEVICE '{}'", name);
+        return Ok(ExitCode::NoMatch);
+    }
+
+    /* The name is configured, but it can't be assigned by hw address */
+    for mapping in &mappings {
+        if let Some(reason) = &mapping.reason {
+            warn!(
+                "'{}' sets DEVICE '{}', but {}",
+                mapping.file.display(),
+                name,
+                reason
+            );
+        }
+    }
+
+    if mappings.iter().all(|mapping| mapping.hwaddr.is_none()) {
+        error!(
+            "No ifcfg file sets DEVICE '{}' together with usable HWADDR",
+            name
+        );
+        return Ok(ExitCode::ConfigError);
+    }
+
+    Ok(ExitCode::Found)
+}
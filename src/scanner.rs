@@ -1,34 +1,204 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use std::path::Path;
+use std::error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
 
-use glob::glob_with;
+use log::*;
 
-/* Scan directory /etc/sysconfig/network-scripts for ifcfg files */
-pub fn config_dir(config_dir: &Path) -> Option<Vec<String>> {
-    let glob_options = glob::MatchOptions {
-        case_sensitive: true,
-        require_literal_separator: false,
-        require_literal_leading_dot: false,
-    };
+use ifcfg_devname::exit::ExitCode;
+use ifcfg_devname::parser::FileKind;
+use ifcfg_devname::report::IgnoredFile;
+use ifcfg_devname::IfcfgFile;
 
-    let glob_pattern = config_dir.to_str()?.to_owned() + "/ifcfg-*";
+/* Suffixes of backup, editor and package-manager leftovers
+ * union of lists used by initscripts (is_ignored_file) and NetworkManager's ifcfg-rh plugin */
+const IGNORED_SUFFIXES: &[&str] = &[
+    "~", ".bak", ".old", ".orig", ".rej", ".rpmnew", ".rpmorig", ".rpmsave", ".augnew", ".augtmp",
+    ".swp",
+];
 
-    let mut config_paths = vec![];
+/* Return suffix because of which the file should be ignored */
+pub fn ignored_suffix<'a>(path: &Path, extra_suffixes: &'a [String]) -> Option<&'a str> {
+    let file_name = path.file_name()?.to_string_lossy();
+
+    IGNORED_SUFFIXES
+        .iter()
+        .copied()
+        .chain(extra_suffixes.iter().map(String::as_str))
+        .find(|suffix| !suffix.is_empty() && file_name.ends_with(suffix))
+}
+
+/* Reasons why list of ifcfg files can't be obtained */
+#[derive(Debug)]
+pub enum ScanError {
+    NotFound(PathBuf),
+    PermissionDenied(PathBuf),
+    NotADirectory(PathBuf),
+    Io(PathBuf, io::Error),
+    NoMatches(PathBuf),
+}
+
+/* Missing ifcfg files means nothing is configured, any other failure means configuration can't be read */
+impl From<&ScanError> for ExitCode {
+    fn from(err: &ScanError) -> Self {
+        match err {
+            ScanError::NoMatches(_) => ExitCode::NoMatch,
+            _ => ExitCode::ConfigError,
+        }
+    }
+}
 
-    for entry in glob_with(&glob_pattern, glob_options).unwrap() {
-        match entry {
-            Ok(path) => {
-                config_paths.push(path.to_str()?.to_owned());
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScanError::NotFound(path) => {
+                write!(f, "directory '{}' doesn't exist", path.display())
             }
-            Err(_err) => continue,
-        };
+            ScanError::PermissionDenied(path) => {
+                write!(f, "permission denied to read '{}'", path.display())
+            }
+            ScanError::NotADirectory(path) => {
+                write!(f, "'{}' isn't a directory", path.display())
+            }
+            ScanError::Io(path, err) => write!(f, "fail to read '{}': {}", path.display(), err),
+            ScanError::NoMatches(path) => {
+                write!(f, "no ifcfg files found in directory '{}'", path.display())
+            }
+        }
+    }
+}
+
+impl error::Error for ScanError {}
+
+impl ScanError {
+    fn from_io(path: &Path, err: io::Error) -> ScanError {
+        let path = path.to_path_buf();
+
+        match err.kind() {
+            io::ErrorKind::NotFound => ScanError::NotFound(path),
+            io::ErrorKind::PermissionDenied => ScanError::PermissionDenied(path),
+            io::ErrorKind::NotADirectory => ScanError::NotADirectory(path),
+            _ => ScanError::Io(path, err),
+        }
+    }
+}
+
+/* Scan directory /etc/sysconfig/network-scripts for ifcfg files
+ * paths are sorted, and they don't have to be valid UTF-8 */
+pub fn config_dir(
+    config_dir: &Path,
+    extra_ignored_suffixes: &[String],
+) -> Result<Vec<PathBuf>, ScanError> {
+    let mut config_paths = vec![];
+
+    for entry in fs::read_dir(config_dir).map_err(|err| ScanError::from_io(config_dir, err))? {
+        let path = entry
+            .map_err(|err| ScanError::from_io(config_dir, err))?
+            .path();
+
+        match path.file_name() {
+            Some(file_name) if file_name.as_bytes().starts_with(b"ifcfg-") => {}
+            _ => continue,
+        }
+
+        if let Some(suffix) = ignored_suffix(&path, extra_ignored_suffixes) {
+            debug!(
+                "Ignoring '{}': files ending with '{}' aren't ifcfg files",
+                path.display(),
+                suffix
+            );
+            continue;
+        }
+
+        /* Alias and range files are still scanned, so misconfigured ones can be reported */
+        let kind = FileKind::from_path(&path);
+        if kind != FileKind::Interface {
+            debug!(
+                "'{}' is {} file, it won't be used for naming",
+                path.display(),
+                kind
+            );
+        }
+
+        config_paths.push(path);
     }
 
-    if !config_paths.is_empty() {
-        Some(config_paths)
-    } else {
-        None
+    if config_paths.is_empty() {
+        return Err(ScanError::NoMatches(config_dir.to_path_buf()));
+    }
+
+    config_paths.sort();
+
+    Ok(config_paths)
+}
+
+/* ifcfg files skipped because of their suffix, they are only reported, so errors are ignored */
+pub fn ignored_files(config_dir: &Path, extra_ignored_suffixes: &[String]) -> Vec<IgnoredFile> {
+    let entries = match fs::read_dir(config_dir) {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+
+    let mut ignored: Vec<IgnoredFile> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .is_some_and(|file_name| file_name.as_bytes().starts_with(b"ifcfg-"))
+        })
+        .filter_map(|path| {
+            let suffix = ignored_suffix(&path, extra_ignored_suffixes)?.to_owned();
+            Some(IgnoredFile { path, suffix })
+        })
+        .collect();
+
+    ignored.sort_by(|a, b| a.path.cmp(&b.path));
+
+    ignored
+}
+
+/* Scan directory and parse all ifcfg files, files that can't be read are kept with their error */
+pub fn load(
+    config_dir: &Path,
+    extra_ignored_suffixes: &[String],
+) -> Result<Vec<IfcfgFile>, ScanError> {
+    let mut ifcfgs = vec![];
+
+    for path in self::config_dir(config_dir, extra_ignored_suffixes)? {
+        let ifcfg = IfcfgFile::load(&path);
+
+        match ifcfg.read_error() {
+            Some(err) => warn!("{}", err),
+            None => warn_non_canonical_hwaddr(&ifcfg),
+        }
+
+        ifcfgs.push(ifcfg);
+    }
+
+    Ok(ifcfgs)
+}
+
+/* hw addresses in other notations are accepted, but they should be written as colon-separated octets */
+fn warn_non_canonical_hwaddr(ifcfg: &IfcfgFile) {
+    for key in ["HWADDR", "MACADDR"] {
+        if let Some(entry) = ifcfg.entry(key) {
+            if let Ok((hwaddr, notation)) = entry.hwaddr() {
+                if !notation.is_canonical() {
+                    warn!(
+                        "{}:{}: {} is written as {}, use '{}' instead",
+                        ifcfg.path().display(),
+                        entry.line,
+                        key,
+                        notation,
+                        hwaddr
+                    );
+                }
+            }
+        }
     }
 }
 
@@ -36,20 +206,91 @@ pub fn config_dir(config_dir: &Path) -> Option<Vec<String>> {
 pub mod should {
     use super::*;
 
+    use ifcfg_devname::parser::ParseError;
+    use ifcfg_devname::resolver::{self, Outcome};
+    use ifcfg_devname::HwAddress;
+
     const TEST_CONFIG_DIR: &str = "./tests/unit_test_data/ifcfgs";
 
     #[test]
     fn scan_config_dir() {
         let ifcfg_dir_path = Path::new(TEST_CONFIG_DIR);
 
-        let test_result = match config_dir(ifcfg_dir_path) {
-            Some(result) => result.eq(&vec![
-                "tests/unit_test_data/ifcfgs/ifcfg-eth0",
-                "tests/unit_test_data/ifcfgs/ifcfg-eth1",
+        let test_result = match config_dir(ifcfg_dir_path, &[]) {
+            Ok(result) => result.eq(&vec![
+                ifcfg_dir_path.join("ifcfg-eth0"),
+                ifcfg_dir_path.join("ifcfg-eth1"),
             ]),
             _ => false,
         };
 
         assert!(test_result);
     }
+
+    #[test]
+    fn ignore_extra_suffixes() {
+        let ifcfg_dir_path = Path::new(TEST_CONFIG_DIR);
+
+        let test_result = match config_dir(ifcfg_dir_path, &[String::from("eth1")]) {
+            Ok(result) => result.eq(&vec![ifcfg_dir_path.join("ifcfg-eth0")]),
+            _ => false,
+        };
+
+        assert!(test_result);
+    }
+
+    #[test]
+    fn list_ignored_files() {
+        let ifcfg_dir_path = Path::new(TEST_CONFIG_DIR);
+
+        let ignored: Vec<(PathBuf, String)> = ignored_files(ifcfg_dir_path, &[])
+            .into_iter()
+            .map(|file| (file.path, file.suffix))
+            .collect();
+
+        assert_eq!(
+            ignored,
+            vec![
+                (ifcfg_dir_path.join("ifcfg-eth0.bak"), String::from(".bak")),
+                (
+                    ifcfg_dir_path.join("ifcfg-eth1.rpmsave"),
+                    String::from(".rpmsave")
+                ),
+                (ifcfg_dir_path.join("ifcfg-eth1~"), String::from("~")),
+            ]
+        );
+    }
+
+    #[test]
+    fn keep_unreadable_files() {
+        let ifcfg_dir_path = Path::new("./tests/unit_test_data/unreadable");
+        let mac_address: HwAddress = "52:54:00:00:00:01".parse().unwrap();
+
+        let ifcfgs = load(ifcfg_dir_path, &[]).unwrap();
+        let resolution = resolver::resolve(&ifcfgs, &mac_address);
+
+        assert_eq!(resolution.candidates.len(), 2);
+        assert!(matches!(
+            resolution.candidates[0].outcome,
+            Outcome::Matched("lan0")
+        ));
+        assert!(matches!(
+            &resolution.candidates[1].outcome,
+            Outcome::Error(ParseError::Io { path, .. }) if path == &ifcfg_dir_path.join("ifcfg-lan1")
+        ));
+    }
+
+    #[test]
+    fn not_scan_missing_config_dir() {
+        let ifcfg_dir_path = Path::new(TEST_CONFIG_DIR).join("missing");
+
+        assert!(matches!(
+            config_dir(&ifcfg_dir_path, &[]),
+            Err(ScanError::NotFound(_))
+        ));
+        assert!(matches!(
+            config_dir(&Path::new(TEST_CONFIG_DIR).join("ifcfg-eth0"), &[]),
+            Err(ScanError::NotADirectory(_))
+        ));
+    }
 }
@@ -0,0 +1,252 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::error;
+use std::fmt;
+
+/* Reasons why the value of an ifcfg assignment can't be read the way `ifup` (bash) would source it */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnescapeError {
+    UnterminatedQuote,
+    TrailingBackslash,
+    UnsupportedExpansion(char),
+    UnsupportedMetacharacter(char),
+    MultipleWords,
+    NulCharacter,
+}
+
+impl fmt::Display for UnescapeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UnescapeError::UnterminatedQuote => write!(f, "unterminated quote"),
+            UnescapeError::TrailingBackslash => write!(f, "trailing backslash"),
+            UnescapeError::UnsupportedExpansion(c) => {
+                write!(f, "shell expansion using '{}' is not supported", c)
+            }
+            UnescapeError::UnsupportedMetacharacter(c) => {
+                write!(f, "unquoted shell metacharacter '{}'", c)
+            }
+            UnescapeError::MultipleWords => write!(f, "value consists of multiple words"),
+            UnescapeError::NulCharacter => write!(f, "value contains NUL character"),
+        }
+    }
+}
+
+impl error::Error for UnescapeError {}
+
+/* Unescape value of ifcfg assignment (everything after `KEY=`) the same way as NetworkManager's svUnescape() does.
+ * ifcfg files are sourced by bash, so the supported syntax is a subset of bash quoting:
+ * https://www.gnu.org/software/bash/manual/html_node/Quoting.html
+ * example: DEVICE="lan 0"   DEVICE='lan0'   DEVICE=$'lan\x30'   DEVICE=lan\ 0   DEVICE=lan0 # comment
+ *                 ^^^^^^^          ^^^^^^          ^^^^^^^^^^          ^^^^^^          ^^^^
+ *                 `lan 0`          `lan0`          `lan0`              `lan 0`         `lan0` */
+pub fn unescape(value: &str) -> Result<String, UnescapeError> {
+    let mut chars = value.chars().peekable();
+    let mut unescaped = String::new();
+
+    while let Some(c) = chars.next() {
+        match c {
+            /* Unquoted whitespace or `;` ends the value, only whitespace, one `;` or comment may follow */
+            c if c.is_ascii_whitespace() || c == ';' => {
+                let mut has_semicolon = c == ';';
+                while let Some(&next) = chars.peek() {
+                    if next.is_ascii_whitespace() || (next == ';' && !has_semicolon) {
+                        has_semicolon |= next == ';';
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+
+                return match chars.peek() {
+                    None | Some('#') => Ok(unescaped),
+                    Some(_) => Err(UnescapeError::MultipleWords),
+                };
+            }
+
+            /* Backslash outside of quotes preserves the literal value of the next character */
+            '\\' => match chars.next() {
+                Some(next) => unescaped.push(next),
+                None => return Err(UnescapeError::TrailingBackslash),
+            },
+
+            /* Single quotes preserve the literal value of each character */
+            '\'' => loop {
+                match chars.next() {
+                    Some('\'') => break,
+                    Some(next) => unescaped.push(next),
+                    None => return Err(UnescapeError::UnterminatedQuote),
+                }
+            },
+
+            /* Double quotes preserve the literal value of each character except `$`, `` ` `` and `\` */
+            '"' => loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => match chars.next() {
+                        Some(next @ ('$' | '`' | '"' | '\\')) => unescaped.push(next),
+                        Some('\n') => {}
+                        Some(next) => {
+                            unescaped.push('\\');
+                            unescaped.push(next);
+                        }
+                        None => return Err(UnescapeError::UnterminatedQuote),
+                    },
+                    Some(next @ ('$' | '`')) => {
+                        return Err(UnescapeError::UnsupportedExpansion(next))
+                    }
+                    Some(next) => unescaped.push(next),
+                    None => return Err(UnescapeError::UnterminatedQuote),
+                }
+            },
+
+            /* ANSI-C quoting $'...' */
+            '$' if chars.peek() == Some(&'\'') => {
+                chars.next();
+                unescape_ansi_c(&mut chars, &mut unescaped)?;
+            }
+
+            '$' | '`' => return Err(UnescapeError::UnsupportedExpansion(c)),
+
+            '|' | '&' | '(' | ')' | '<' | '>' => {
+                return Err(UnescapeError::UnsupportedMetacharacter(c))
+            }
+
+            c => unescaped.push(c),
+        }
+    }
+
+    if unescaped.contains('\0') {
+        return Err(UnescapeError::NulCharacter);
+    }
+
+    Ok(unescaped)
+}
+
+/* Decode content of $'...' string up to and including the closing quote */
+fn unescape_ansi_c<I>(
+    chars: &mut std::iter::Peekable<I>,
+    unescaped: &mut String,
+) -> Result<(), UnescapeError>
+where
+    I: Iterator<Item = char>,
+{
+    loop {
+        let c = match chars.next() {
+            Some('\'') => return Ok(()),
+            Some(c) => c,
+            None => return Err(UnescapeError::UnterminatedQuote),
+        };
+
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        let escaped = match chars.next() {
+            Some(escaped) => escaped,
+            None => return Err(UnescapeError::UnterminatedQuote),
+        };
+
+        let decoded = match escaped {
+            'a' => '\x07',
+            'b' => '\x08',
+            'e' | 'E' => '\x1b',
+            'f' => '\x0c',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'v' => '\x0b',
+            '\\' | '\'' | '"' | '?' => escaped,
+            '0'..='7' => {
+                let mut code = escaped.to_digit(8).unwrap();
+                code = take_digits(chars, 8, 2, code);
+                char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER)
+            }
+            'x' | 'u' | 'U' => {
+                let max_digits = match escaped {
+                    'x' => 2,
+                    'u' => 4,
+                    _ => 8,
+                };
+
+                if !chars.peek().is_some_and(|c| c.is_ascii_hexdigit()) {
+                    unescaped.push('\\');
+                    unescaped.push(escaped);
+                    continue;
+                }
+
+                let code = take_digits(chars, 16, max_digits, 0);
+                char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER)
+            }
+            _ => {
+                unescaped.push('\\');
+                escaped
+            }
+        };
+
+        if decoded == '\0' {
+            return Err(UnescapeError::NulCharacter);
+        }
+
+        unescaped.push(decoded);
+    }
+}
+
+/* Consume up to max_digits digits of given radix and accumulate them into code */
+fn take_digits<I>(
+    chars: &mut std::iter::Peekable<I>,
+    radix: u32,
+    max_digits: usize,
+    mut code: u32,
+) -> u32
+where
+    I: Iterator<Item = char>,
+{
+    for _ in 0..max_digits {
+        match chars.peek().and_then(|c| c.to_digit(radix)) {
+            Some(digit) => {
+                code = code * radix + digit;
+                chars.next();
+            }
+            None => break,
+        }
+    }
+
+    code
+}
+
+#[cfg(test)]
+pub mod should {
+    use super::*;
+
+    #[test]
+    fn unescape_quoted_values() {
+        assert_eq!(unescape("lan0").unwrap(), "lan0");
+        assert_eq!(unescape("\"lan0\"").unwrap(), "lan0");
+        assert_eq!(unescape("'lan0'").unwrap(), "lan0");
+        assert_eq!(unescape("\"lan \\\"0\\\"\"").unwrap(), "lan \"0\"");
+        assert_eq!(unescape("'lan\\0'").unwrap(), "lan\\0");
+        assert_eq!(unescape("lan\\ 0").unwrap(), "lan 0");
+        assert_eq!(unescape("$'lan\\x30\\t'").unwrap(), "lan0\t");
+        assert_eq!(unescape("l'a'\"n\"0").unwrap(), "lan0");
+        assert_eq!(unescape("lan0 # comment").unwrap(), "lan0");
+        assert_eq!(unescape("lan0;").unwrap(), "lan0");
+        assert_eq!(unescape("").unwrap(), "");
+    }
+
+    #[test]
+    fn not_unescape_invalid_values() {
+        assert_eq!(unescape("\"lan0"), Err(UnescapeError::UnterminatedQuote));
+        assert_eq!(unescape("lan0\\"), Err(UnescapeError::TrailingBackslash));
+        assert_eq!(
+            unescape("\"$DEVICE\""),
+            Err(UnescapeError::UnsupportedExpansion('$'))
+        );
+        assert_eq!(
+            unescape("lan0|cat"),
+            Err(UnescapeError::UnsupportedMetacharacter('|'))
+        );
+        assert_eq!(unescape("lan0 ls"), Err(UnescapeError::MultipleWords));
+        assert_eq!(unescape("$'lan\\0'"), Err(UnescapeError::NulCharacter));
+    }
+}
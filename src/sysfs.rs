@@ -0,0 +1,279 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::{HwAddress, MacLookupError};
+
+/* Default mount point of sysfs */
+pub const SYSFS_ROOT: &str = "/sys";
+
+/* How hw address of network interface was assigned, see NET_ADDR_* in <linux/netdevice.h> */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddrAssignType {
+    /* Address is permanent (burned-in) */
+    Permanent,
+    /* Address was generated randomly, e.g. driver failed to read EEPROM */
+    Random,
+    /* Address was stolen from other device, e.g. bond or team */
+    Stolen,
+    /* Address was set from user space, e.g. using MACADDR */
+    Set,
+}
+
+impl AddrAssignType {
+    pub fn from_raw(value: u32) -> Option<AddrAssignType> {
+        match value {
+            0 => Some(AddrAssignType::Permanent),
+            1 => Some(AddrAssignType::Random),
+            2 => Some(AddrAssignType::Stolen),
+            3 => Some(AddrAssignType::Set),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for AddrAssignType {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "permanent" => Ok(AddrAssignType::Permanent),
+            "random" => Ok(AddrAssignType::Random),
+            "stolen" => Ok(AddrAssignType::Stolen),
+            "set" => Ok(AddrAssignType::Set),
+            _ => Err(format!(
+                "unknown address assign type '{}', expected 'permanent', 'random', 'stolen' or 'set'",
+                value
+            )),
+        }
+    }
+}
+
+impl fmt::Display for AddrAssignType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AddrAssignType::Permanent => write!(f, "permanent"),
+            AddrAssignType::Random => write!(f, "random"),
+            AddrAssignType::Stolen => write!(f, "stolen"),
+            AddrAssignType::Set => write!(f, "set"),
+        }
+    }
+}
+
+/* Attributes of network interface exported in /sys/class/net/<interface>/ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interface {
+    pub name: String,
+    /* None when interface has no link-layer address (tun, wireguard, etc.) */
+    pub address: Option<HwAddress>,
+    pub addr_assign_type: Option<AddrAssignType>,
+    /* Type of link-layer (ARPHRD_*), see <linux/if_arp.h> */
+    pub link_type: Option<u16>,
+    /* Port number of multi-port device */
+    pub dev_port: Option<u32>,
+    pub dev_id: Option<u32>,
+    /* Target of `device` symlink, virtual interfaces have none */
+    pub device: Option<PathBuf>,
+}
+
+/* Reader of network interface attributes from sysfs mounted under given root, tests point it to fixtures */
+#[derive(Debug, Clone)]
+pub struct Sysfs {
+    root: PathBuf,
+}
+
+impl Default for Sysfs {
+    fn default() -> Self {
+        Sysfs::new(Path::new(SYSFS_ROOT))
+    }
+}
+
+impl Sysfs {
+    pub fn new(root: &Path) -> Sysfs {
+        Sysfs {
+            root: root.to_path_buf(),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /* sysfs of the running kernel, not a copy of other one */
+    pub fn is_host(&self) -> bool {
+        self.root == Path::new(SYSFS_ROOT)
+    }
+
+    pub fn interface(&self, name: &str) -> Result<Interface, MacLookupError> {
+        /* Names the kernel would refuse can't exist, and they could point outside of /sys/class/net/ */
+        if crate::validate_devname(name).is_err() {
+            return Err(MacLookupError::NotFound(name.to_owned()));
+        }
+
+        let address = fs::read_to_string(self.attribute_path(name, "address")).map_err(|err| {
+            match err.kind() {
+                io::ErrorKind::NotFound => MacLookupError::NotFound(name.to_owned()),
+                io::ErrorKind::PermissionDenied => {
+                    MacLookupError::PermissionDenied(name.to_owned())
+                }
+                _ => MacLookupError::Io(name.to_owned(), err),
+            }
+        })?;
+
+        Ok(Interface {
+            name: name.to_owned(),
+            address: parse_address(&address),
+            addr_assign_type: self
+                .attribute(name, "addr_assign_type")
+                .and_then(|value| value.parse().ok())
+                .and_then(AddrAssignType::from_raw),
+            link_type: self
+                .attribute(name, "type")
+                .and_then(|value| value.parse().ok()),
+            dev_port: self
+                .attribute(name, "dev_port")
+                .and_then(|value| value.parse().ok()),
+            /* dev_id is printed in hexadecimal with 0x prefix */
+            dev_id: self
+                .attribute(name, "dev_id")
+                .and_then(|value| u32::from_str_radix(value.trim_start_matches("0x"), 16).ok()),
+            device: fs::read_link(self.attribute_path(name, "device")).ok(),
+        })
+    }
+
+    /* Names of all network interfaces, sorted */
+    pub fn interfaces(&self) -> io::Result<Vec<String>> {
+        let mut names: Vec<String> = fs::read_dir(self.root.join("class").join("net"))?
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .collect();
+
+        names.sort();
+
+        Ok(names)
+    }
+
+    /* Current name of interface with given index, index doesn't change when interface is renamed */
+    pub fn name_by_ifindex(&self, ifindex: u32) -> Option<String> {
+        fs::read_dir(self.root.join("class").join("net"))
+            .ok()?
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .find(|name| {
+                self.attribute(name, "ifindex")
+                    .and_then(|value| value.parse().ok())
+                    == Some(ifindex)
+            })
+    }
+
+    /* Name of interface at given DEVPATH, None when it was renamed or removed meanwhile */
+    pub fn name_by_devpath(&self, devpath: &Path) -> Option<String> {
+        let path = self.root.join(devpath.strip_prefix("/").unwrap_or(devpath));
+
+        if !path.join("ifindex").is_file() {
+            return None;
+        }
+
+        path.file_name()?.to_str().map(String::from)
+    }
+
+    /* Permanent hw address, only some kernels export it */
+    pub fn permanent_address(&self, name: &str) -> Option<HwAddress> {
+        self.attribute(name, "perm_addr")
+            .and_then(|value| parse_address(&value))
+    }
+
+    /* Value of single attribute, None when it doesn't exist or can't be read */
+    pub fn attribute(&self, name: &str, attribute: &str) -> Option<String> {
+        fs::read_to_string(self.attribute_path(name, attribute))
+            .ok()
+            .map(|value| value.trim().to_owned())
+    }
+
+    fn attribute_path(&self, name: &str, attribute: &str) -> PathBuf {
+        self.root
+            .join("class")
+            .join("net")
+            .join(name)
+            .join(attribute)
+    }
+}
+
+/* Empty or all zeroes hw address means interface has no link-layer address */
+fn parse_address(value: &str) -> Option<HwAddress> {
+    match value.trim().parse::<HwAddress>() {
+        Ok(address) if address.bytes().iter().any(|byte| *byte != 0) => Some(address),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+pub mod should {
+    use super::*;
+
+    const TEST_SYSFS_ROOT: &str = "./tests/unit_test_data/sysfs";
+
+    #[test]
+    fn inspect_sysfs_interface() {
+        let sysfs = Sysfs::new(Path::new(TEST_SYSFS_ROOT));
+
+        let ethernet = sysfs.interface("enp0s31f6").unwrap();
+        let tunnel = sysfs.interface("tun0").unwrap();
+
+        assert_eq!(ethernet.address, "00:1b:44:11:3a:b7".parse().ok());
+        assert_eq!(ethernet.addr_assign_type, Some(AddrAssignType::Permanent));
+        assert_eq!(ethernet.link_type, Some(1));
+        assert_eq!(ethernet.dev_port, Some(0));
+        assert_eq!(ethernet.dev_id, Some(0));
+        assert_eq!(
+            ethernet.device,
+            Some(PathBuf::from("../../../devices/pci0000:00/0000:00:1f.6"))
+        );
+
+        assert_eq!(tunnel.address, None);
+        assert_eq!(tunnel.addr_assign_type, Some(AddrAssignType::Random));
+        assert_eq!(tunnel.link_type, Some(65534));
+        assert_eq!(tunnel.device, None);
+    }
+
+    #[test]
+    fn find_interface_by_ifindex_and_devpath() {
+        let sysfs = Sysfs::new(Path::new(TEST_SYSFS_ROOT));
+
+        assert_eq!(
+            sysfs.interfaces().unwrap(),
+            vec![
+                String::from("enp0s31f6"),
+                String::from("ib0"),
+                String::from("tun0")
+            ]
+        );
+
+        assert_eq!(sysfs.name_by_ifindex(5), Some(String::from("tun0")));
+        assert_eq!(sysfs.name_by_ifindex(42), None);
+        assert_eq!(
+            sysfs.name_by_devpath(Path::new("/devices/pci0000:00/0000:00:1f.6/net/enp0s31f6")),
+            Some(String::from("enp0s31f6"))
+        );
+        assert_eq!(
+            sysfs.name_by_devpath(Path::new("/devices/pci0000:00/0000:00:1f.6/net/eth0")),
+            None
+        );
+    }
+
+    #[test]
+    fn not_inspect_missing_interface() {
+        let sysfs = Sysfs::new(Path::new(TEST_SYSFS_ROOT));
+
+        assert!(matches!(
+            sysfs.interface("eth9"),
+            Err(MacLookupError::NotFound(_))
+        ));
+        assert!(matches!(
+            sysfs.interface("../../.."),
+            Err(MacLookupError::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,207 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::env;
+use std::error;
+use std::ffi::OsString;
+use std::fmt;
+use std::path::PathBuf;
+
+use log::*;
+
+use crate::Sysfs;
+
+/* Reasons why network interface of udev event can't be determined */
+#[derive(Debug, PartialEq, Eq)]
+pub enum UdevError {
+    MissingInterface,
+    /* Interface was found neither by IFINDEX nor by DEVPATH and INTERFACE isn't valid UTF-8 */
+    NotUnicode(OsString),
+}
+
+impl fmt::Display for UdevError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UdevError::MissingInterface => {
+                write!(f, "Fail to obtain environment variable 'INTERFACE'")
+            }
+            UdevError::NotUnicode(value) => write!(
+                f,
+                "environment variable 'INTERFACE' isn't valid UTF-8: {}",
+                value.to_string_lossy()
+            ),
+        }
+    }
+}
+
+impl error::Error for UdevError {}
+
+/* Environment udev passes to helper programs, all values are missing when run by hand */
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct UdevEvent {
+    pub action: Option<String>,
+    pub subsystem: Option<String>,
+    /* Kernel name of the interface, it isn't necessarily valid UTF-8 */
+    pub interface: Option<OsString>,
+    /* Index of the interface, unlike the name it never changes */
+    pub ifindex: Option<u32>,
+    /* Path of the device relative to sysfs root, e.g. /devices/pci0000:00/0000:00:1f.6/net/eth0 */
+    pub devpath: Option<PathBuf>,
+}
+
+impl UdevEvent {
+    pub fn from_env() -> UdevEvent {
+        UdevEvent::from_vars(|name| env::var_os(name))
+    }
+
+    pub fn from_vars<F>(var: F) -> UdevEvent
+    where
+        F: Fn(&str) -> Option<OsString>,
+    {
+        let lossy = |name: &str| var(name).map(|value| value.to_string_lossy().into_owned());
+
+        UdevEvent {
+            action: lossy("ACTION"),
+            subsystem: lossy("SUBSYSTEM"),
+            interface: var("INTERFACE"),
+            ifindex: lossy("IFINDEX").and_then(|value| {
+                value
+                    .parse()
+                    .map_err(|_| warn!("Ignoring invalid IFINDEX '{}'", value))
+                    .ok()
+            }),
+            devpath: var("DEVPATH").map(PathBuf::from),
+        }
+    }
+
+    /* Only network interfaces that were added or moved (renamed) need a name */
+    pub fn is_relevant(&self) -> bool {
+        let action = matches!(self.action.as_deref(), None | Some("add") | Some("move"));
+        let subsystem = matches!(self.subsystem.as_deref(), None | Some("net"));
+
+        action && subsystem
+    }
+
+    /* Current name of the interface, IFINDEX and DEVPATH aren't affected by renames done meanwhile */
+    pub fn interface_name(&self, sysfs: &Sysfs) -> Result<String, UdevError> {
+        if let Some(name) = self
+            .ifindex
+            .and_then(|ifindex| sysfs.name_by_ifindex(ifindex))
+        {
+            return Ok(name);
+        }
+
+        if let Some(name) = self
+            .devpath
+            .as_deref()
+            .and_then(|devpath| sysfs.name_by_devpath(devpath))
+        {
+            return Ok(name);
+        }
+
+        match &self.interface {
+            Some(interface) => interface
+                .clone()
+                .into_string()
+                .map_err(UdevError::NotUnicode),
+            None => Err(UdevError::MissingInterface),
+        }
+    }
+}
+
+/* Format value of property printed for IMPORT{program}
+ * Safe values are printed as they are, others are double-quoted. udev only strips one pair of outer
+ * quotes and never unescapes anything, so values with quotes, backslashes or control characters
+ * can't be passed through and None is returned */
+pub fn quote_property(value: &str) -> Option<String> {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "_-.:/@+,%".contains(c);
+
+    if !value.is_empty() && value.chars().all(is_safe) {
+        return Some(value.to_owned());
+    }
+
+    if value
+        .chars()
+        .any(|c| c == '"' || c == '\\' || c.is_control())
+    {
+        return None;
+    }
+
+    Some(format!("\"{}\"", value))
+}
+
+#[cfg(test)]
+pub mod should {
+    use super::*;
+
+    use std::collections::HashMap;
+    use std::os::unix::ffi::OsStringExt;
+    use std::path::Path;
+
+    const TEST_SYSFS_ROOT: &str = "./tests/unit_test_data/sysfs";
+
+    fn event(vars: &[(&str, OsString)]) -> UdevEvent {
+        let vars: HashMap<&str, OsString> = vars.iter().cloned().collect();
+        UdevEvent::from_vars(|name| vars.get(name).cloned())
+    }
+
+    #[test]
+    fn build_udev_event() {
+        let event = event(&[
+            ("ACTION", "add".into()),
+            ("SUBSYSTEM", "net".into()),
+            ("INTERFACE", "eth0".into()),
+            ("IFINDEX", "2".into()),
+            (
+                "DEVPATH",
+                "/devices/pci0000:00/0000:00:1f.6/net/eth0".into(),
+            ),
+        ]);
+
+        assert!(event.is_relevant());
+        assert_eq!(event.ifindex, Some(2));
+        assert_eq!(
+            event.interface_name(&Sysfs::new(Path::new(TEST_SYSFS_ROOT))),
+            Ok(String::from("enp0s31f6"))
+        );
+    }
+
+    #[test]
+    fn ignore_irrelevant_events() {
+        assert!(event(&[]).is_relevant());
+        assert!(event(&[("ACTION", "move".into())]).is_relevant());
+        assert!(!event(&[("ACTION", "remove".into())]).is_relevant());
+        assert!(!event(&[("ACTION", "add".into()), ("SUBSYSTEM", "block".into())]).is_relevant());
+    }
+
+    #[test]
+    fn not_resolve_non_unicode_interface() {
+        let sysfs = Sysfs::new(Path::new(TEST_SYSFS_ROOT));
+        let name = OsString::from_vec(b"eth\xff".to_vec());
+
+        assert_eq!(
+            event(&[("INTERFACE", name.clone())]).interface_name(&sysfs),
+            Err(UdevError::NotUnicode(name))
+        );
+        assert_eq!(
+            event(&[("IFINDEX", "x".into())]).interface_name(&sysfs),
+            Err(UdevError::MissingInterface)
+        );
+    }
+
+    #[test]
+    fn quote_udev_property() {
+        assert_eq!(quote_property("lan0").unwrap(), "lan0");
+        assert_eq!(
+            quote_property("/etc/sysconfig/network-scripts/ifcfg-lan0").unwrap(),
+            "/etc/sysconfig/network-scripts/ifcfg-lan0"
+        );
+        assert_eq!(quote_property("").unwrap(), "\"\"");
+        assert_eq!(
+            quote_property("/etc/sysconfig/ifcfg-my lan0").unwrap(),
+            "\"/etc/sysconfig/ifcfg-my lan0\""
+        );
+        assert_eq!(quote_property("ifcfg-my \"lan0\""), None);
+        assert_eq!(quote_property("ifcfg-lan0\n"), None);
+        assert_eq!(quote_property("a\\b"), None);
+    }
+}
@@ -1,5 +1,6 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use std::collections::HashMap;
 use std::fs::{self};
 use std::path::Path;
 
@@ -7,6 +8,8 @@ use std::path::Path;
 use assert_cmd::Command;
 use predicates::prelude::*;
 
+use ifcfg_devname::exit::ExitCode;
+
 /* JSON */
 use serde::{Deserialize, Serialize};
 
@@ -23,13 +26,25 @@ struct Dataset {
 #[derive(Serialize, Deserialize)]
 struct DatasetInput {
     interface: String,
-    hw_address: String,
+    /* Without hw address it's read from sysfs, see IFCFG_DEVNAME_SYSFS_ROOT, every dataset sets its own */
+    #[serde(default)]
+    hw_address: Option<String>,
+    #[serde(default)]
+    env: HashMap<String, String>,
+    /* Extra command-line options, e.g. output format */
+    #[serde(default)]
+    args: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize)]
 struct DatasetOutput {
     should_fail: bool,
     expected_name: String,
+    /* See ExitCode in src/exit.rs */
+    exit_code: i32,
+    /* Regex standard output has to match even when the dataset fails, e.g. --explain */
+    #[serde(default)]
+    expected_output: Option<String>,
 }
 
 #[test]
@@ -51,22 +66,47 @@ fn integration_test_datasets() -> Result<(), Box<dyn std::error::Error>> {
             let dataset_configuration: Dataset =
                 serde_json::from_str(&fs::read_to_string(config_path)?)?;
 
+            /* Host sysfs would make results depend on interfaces of the machine running tests */
+            assert!(
+                dataset_configuration
+                    .input
+                    .env
+                    .contains_key("IFCFG_DEVNAME_SYSFS_ROOT"),
+                "{}: IFCFG_DEVNAME_SYSFS_ROOT isn't set",
+                path.display()
+            );
+
             /* Run ifcfg-devname with parameters from given dataset */
-            let dataset_assert = cmd
-                .env("INTERFACE", dataset_configuration.input.interface)
-                .args(&[
-                    ifcfgs_dir_path.into_os_string().into_string().unwrap(), /* ifcfgs directory */
-                    dataset_configuration.input.hw_address,                  /* hw address */
-                ])
-                .assert();
+            cmd.env("INTERFACE", dataset_configuration.input.interface)
+                .envs(dataset_configuration.input.env)
+                .arg("--config-dir")
+                .arg(ifcfgs_dir_path); /* ifcfgs directory */
+
+            if let Some(hw_address) = dataset_configuration.input.hw_address {
+                cmd.arg("--mac").arg(hw_address); /* hw address */
+            }
+
+            let dataset_assert = cmd.args(dataset_configuration.input.args).assert();
 
             /* Test result evaluation */
+            let dataset_assert = match dataset_configuration.output.expected_output {
+                Some(expected_output) => {
+                    dataset_assert.stdout(predicate::str::is_match(expected_output)?)
+                }
+                None => dataset_assert,
+            };
+
             if dataset_configuration.output.should_fail {
-                dataset_assert.failure().code(1); /* Expected Error code */
+                dataset_assert
+                    .failure()
+                    .code(dataset_configuration.output.exit_code); /* Expected Error code */
             } else {
-                dataset_assert.success().stdout(predicate::str::is_match(
-                    dataset_configuration.output.expected_name,
-                )?);
+                dataset_assert
+                    .success()
+                    .code(dataset_configuration.output.exit_code)
+                    .stdout(predicate::str::is_match(
+                        dataset_configuration.output.expected_name,
+                    )?);
             }
         }
     }
@@ -78,7 +118,197 @@ fn integration_test_datasets() -> Result<(), Box<dyn std::error::Error>> {
 fn integration_test_no_env() -> Result<(), Box<dyn std::error::Error>> {
     let mut cmd = Command::cargo_bin("ifcfg-devname")?;
 
-    cmd.assert().failure().code(1);
+    cmd.env_remove("INTERFACE")
+        .assert()
+        .failure()
+        .code(ExitCode::Usage.code());
+
+    Ok(())
+}
+
+#[test]
+fn integration_test_legacy_arguments() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("ifcfg-devname")?;
+
+    /* Deprecated positional form <CONFIG_DIR> <HWADDR> still works */
+    cmd.env("INTERFACE", "dataset_2_if")
+        .env(
+            "IFCFG_DEVNAME_SYSFS_ROOT",
+            "./tests/integration_test_data/2/sysfs",
+        )
+        .args([
+            "./tests/integration_test_data/2/ifcfgs",
+            "AA:BB:CC:DD:EE:F2",
+        ])
+        .assert()
+        .success()
+        .stdout("dataset_2\n");
+
+    let mut cmd = Command::cargo_bin("ifcfg-devname")?;
+
+    cmd.env("INTERFACE", "dataset_2_if")
+        .env(
+            "IFCFG_DEVNAME_SYSFS_ROOT",
+            "./tests/integration_test_data/2/sysfs",
+        )
+        .arg("./tests/integration_test_data/2/ifcfgs")
+        .assert()
+        .failure()
+        .code(ExitCode::Usage.code());
+
+    Ok(())
+}
+
+#[test]
+fn integration_test_help_and_version() -> Result<(), Box<dyn std::error::Error>> {
+    Command::cargo_bin("ifcfg-devname")?
+        .arg("--help")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--config-dir"));
+
+    Command::cargo_bin("ifcfg-devname")?
+        .arg("--version")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(env!("CARGO_PKG_VERSION")));
+
+    Command::cargo_bin("ifcfg-devname")?
+        .arg("--no-such-option")
+        .assert()
+        .failure()
+        .code(ExitCode::Usage.code());
+
+    Ok(())
+}
+
+#[test]
+fn integration_test_list() -> Result<(), Box<dyn std::error::Error>> {
+    Command::cargo_bin("ifcfg-devname")?
+        .args([
+            "list",
+            "--config-dir",
+            "./tests/integration_test_data/11/ifcfgs",
+            "--format",
+            "csv",
+        ])
+        .assert()
+        .success()
+        .stdout(
+            "hwaddr,device,file,warnings,reason\n\
+             aa:bb:cc:dd:ee:11,dataset_11,./tests/integration_test_data/11/ifcfgs/ifcfg-eth0,conflict,\n\
+             aa:bb:cc:dd:ee:11,stale_if,./tests/integration_test_data/11/ifcfgs/ifcfg-stale,conflict,\n",
+        );
+
+    Command::cargo_bin("ifcfg-devname")?
+        .args([
+            "list",
+            "--config-dir",
+            "./tests/integration_test_data/3/ifcfgs",
+        ])
+        .assert()
+        .failure()
+        .code(ExitCode::ConfigError.code());
+
+    Ok(())
+}
+
+#[test]
+fn integration_test_lint() -> Result<(), Box<dyn std::error::Error>> {
+    Command::cargo_bin("ifcfg-devname")?
+        .args([
+            "lint",
+            "--config-dir",
+            "./tests/integration_test_data/13/ifcfgs",
+        ])
+        .assert()
+        .success()
+        .stdout("");
+
+    Command::cargo_bin("ifcfg-devname")?
+        .args(["lint", "--config-dir", "./tests/integration_test_data/11/ifcfgs"])
+        .assert()
+        .code(ExitCode::ConfigError.code())
+        .stdout(
+            "./tests/integration_test_data/11/ifcfgs/ifcfg-stale:5: error[E001]: HWADDR 'aa:bb:cc:dd:ee:11' is already set in './tests/integration_test_data/11/ifcfgs/ifcfg-eth0', but with DEVICE 'dataset_11'\n",
+        );
+
+    Ok(())
+}
+
+#[test]
+fn integration_test_reverse() -> Result<(), Box<dyn std::error::Error>> {
+    Command::cargo_bin("ifcfg-devname")?
+        .args([
+            "reverse",
+            "dataset_13",
+            "--config-dir",
+            "./tests/integration_test_data/13/ifcfgs",
+            "--format",
+            "csv",
+        ])
+        .assert()
+        .success()
+        .stdout(
+            "hwaddr,device,file,warnings,reason\n\
+             aa:bb:cc:dd:ee:13,dataset_13,./tests/integration_test_data/13/ifcfgs/ifcfg-eth0,,\n",
+        );
+
+    Command::cargo_bin("ifcfg-devname")?
+        .args([
+            "by-name",
+            "stale_if",
+            "--config-dir",
+            "./tests/integration_test_data/13/ifcfgs",
+        ])
+        .assert()
+        .failure()
+        .code(ExitCode::NoMatch.code());
+
+    /* The name is configured, but HWADDR is commented out */
+    Command::cargo_bin("ifcfg-devname")?
+        .args([
+            "reverse",
+            "old_20",
+            "--config-dir",
+            "./tests/integration_test_data/20/ifcfgs",
+            "--format",
+            "csv",
+        ])
+        .assert()
+        .failure()
+        .code(ExitCode::ConfigError.code())
+        .stdout(
+            "hwaddr,device,file,warnings,reason\n\
+             ,old_20,./tests/integration_test_data/20/ifcfgs/ifcfg-old_20,no-hwaddr,HWADDR is commented out on line 2\n",
+        );
+
+    Ok(())
+}
+
+#[test]
+fn integration_test_plan() -> Result<(), Box<dyn std::error::Error>> {
+    Command::cargo_bin("ifcfg-devname")?
+        .args([
+            "plan",
+            "--config-dir",
+            "./tests/unit_test_data/plan/ifcfgs",
+            "--sysfs-root",
+            "./tests/unit_test_data/plan/sysfs",
+            "--format",
+            "csv",
+        ])
+        .assert()
+        .failure()
+        .code(ExitCode::ConfigError.code())
+        .stdout(predicate::str::contains(
+            "enp1s0,52:54:00:00:00:01,lan0,./tests/unit_test_data/plan/ifcfgs/ifcfg-lan0,rename\n",
+        ))
+        .stdout(predicate::str::contains(
+            "enp4s0,52:54:00:00:00:04,storage0,",
+        ))
+        .stdout(predicate::str::contains("lan1,52:54:00:00:00:02,lan1,"))
+        .stdout(predicate::str::contains("lo,,,,no-address\n"));
 
     Ok(())
 }